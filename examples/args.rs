@@ -0,0 +1,35 @@
+use libphp::{
+    exec::{Args, Context},
+    value::Value,
+    Error,
+};
+
+fn main() -> Result<(), Error> {
+    let mut context = Context::new();
+
+    // Arguments of different types can be passed as a tuple.
+    dbg!(context.call_with("str_pad", ("x", 10, "-"))?);
+
+    // PHP 8 named arguments skip over the optional parameters in between.
+    let args = Args::new()
+        .arg("x")
+        .arg(10)
+        .named("pad_type", context.constant("STR_PAD_LEFT"));
+    dbg!(context.call_with("str_pad", args)?);
+
+    // By-reference parameters write to a reference, which can be read back afterwards.
+    let matches = Value::reference(());
+    let found = context.call_with(
+        "preg_match",
+        (
+            "/(\\w+)@(\\w+)\\.com/",
+            "contact: ryan@example.com",
+            matches.clone(),
+        ),
+    )?;
+    dbg!(found, matches.dereference());
+
+    dbg!(context.call("phpversion")?);
+
+    Ok(())
+}
@@ -1,13 +1,13 @@
-use libphp::exec::Context;
+use libphp::{exec::Context, value::array::Array, Error};
 
-fn main() {
+fn main() -> Result<(), Error> {
     let mut context = Context::new();
-    let array = context.result_of("[1, 2, 3, 4, 5, 'key' => 6]");
+    let array = context.result_of("[1, 2, 3, 4, 5, 'key' => 6]")?;
 
     println!("The array returned is: {array:?}");
     println!("is_array(): {}", array.is_array());
 
-    let array = array.to_array();
+    let array = array.to_array().expect("an array");
 
     println!("array.len(): {}", array.len());
     println!("array.is_empty(): {}", array.is_empty());
@@ -15,4 +15,21 @@ fn main() {
     for (idx, key, value) in array.iter() {
         println!("array[{}] (pos: {}) = {:?}", key, idx, value);
     }
+
+    println!("array['key']: {:?}", array.get("key"));
+    println!("array.contains_key(10): {}", array.contains_key(10));
+
+    // Arrays can be built in Rust and passed into PHP.
+    let mut user: Array = [("name", "Ryan"), ("role", "admin")].into_iter().collect();
+    user.insert("id", 1);
+    user.remove("role");
+
+    let mut tags: Array = ["php", "rust"].into_iter().collect();
+    tags.push("ffi");
+    user.insert("tags", tags);
+
+    context.bind("user", user);
+    dbg!(context.result_of("json_encode($user)")?);
+
+    Ok(())
 }
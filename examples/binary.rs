@@ -0,0 +1,19 @@
+use libphp::{exec::Context, value::PhpString, Error};
+
+fn main() -> Result<(), Error> {
+    let mut context = Context::new();
+    context.init()?;
+
+    // PHP strings are byte buffers, so NUL bytes and invalid UTF-8 survive the round trip.
+    let payload = PhpString::new(b"\x89PNG\r\n\x1a\n\x00\x00\x00\rIHDR");
+
+    dbg!(context.call_with("strlen", &[payload.clone()])?);
+    dbg!(context.call_with("bin2hex", &[payload.clone()])?);
+
+    let reversed = context.call_with("strrev", &[payload])?;
+    let reversed = PhpString::try_from(&reversed).unwrap();
+
+    dbg!(reversed.len(), reversed.as_bytes());
+
+    Ok(())
+}
@@ -1,12 +1,14 @@
-use libphp::exec::Context;
+use libphp::{exec::Context, Error};
 
-fn main() {
+fn main() -> Result<(), Error> {
     let mut context = Context::new();
 
     context.on_init(|ctx| {
         ctx.bind("myVar", "Hello, this variable is defined in Rust!");
     });
 
-    let my_var = context.result_of("$myVar");
+    let my_var = context.result_of("$myVar")?;
     println!("my_var = {:?}", my_var);
+
+    Ok(())
 }
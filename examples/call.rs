@@ -1,16 +1,18 @@
-use libphp::exec::Context;
+use libphp::{exec::Context, Error};
 
-fn main() {
+fn main() -> Result<(), Error> {
     let mut context = Context::new();
 
-    let version_result = context.call("phpversion");
+    let version_result = context.call("phpversion")?;
     dbg!(version_result);
 
-    let strlen_result = context.call_with("strlen", &["Hello, world!"]);
+    let strlen_result = context.call_with("strlen", &["Hello, world!"])?;
     dbg!(strlen_result);
 
-    context.execute_file("./examples/scripts/functions.php");
+    context.execute_file("./examples/scripts/functions.php")?;
 
-    let fib_30_result = context.call_with("fib", &[35]);
+    let fib_30_result = context.call_with("fib", &[35])?;
     dbg!(fib_30_result);
+
+    Ok(())
 }
\ No newline at end of file
@@ -0,0 +1,51 @@
+use libphp::{exec::Context, Error};
+
+fn main() -> Result<(), Error> {
+    let mut context = Context::new();
+
+    context.eval(
+        r#"
+        function parse_port(string $value): int {
+            if (!ctype_digit($value)) {
+                throw new InvalidArgumentException("Invalid port: $value", 22);
+            }
+
+            return (int) $value;
+        }
+
+        function quit(int $status): void {
+            exit($status);
+        }
+        "#,
+    )?;
+
+    // An exception thrown by a called function is returned with its details, just as it is
+    // when thrown by a script.
+    match context.call_with("parse_port", ("http",)) {
+        Err(Error::UncaughtException(exception)) => {
+            println!(
+                "{} (code {}) on line {}: {}",
+                exception.class_name, exception.code, exception.line, exception.message
+            );
+            println!("{}", exception.trace);
+        }
+        other => println!("Unexpected result: {:?}", other),
+    }
+
+    let parse_port = context.callable("parse_port")?;
+
+    match parse_port.call(("-1",)) {
+        Err(Error::UncaughtException(exception)) => println!("Callable threw: {}", exception),
+        other => println!("Unexpected result: {:?}", other),
+    }
+
+    match context.call_with("quit", (3,)) {
+        Err(Error::Exit(status)) => println!("Called function exited with status {}", status),
+        other => println!("Unexpected result: {:?}", other),
+    }
+
+    // The context can keep running code afterwards.
+    dbg!(parse_port.call(("8080",))?);
+
+    Ok(())
+}
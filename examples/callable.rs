@@ -0,0 +1,30 @@
+use libphp::{exec::Context, value::callable::Callable, Error};
+
+fn main() -> Result<(), Error> {
+    let mut context = Context::new();
+
+    // Event handlers registered by the script, keyed by event name.
+    let handlers = context
+        .execute_file("./examples/scripts/events.php")?
+        .to_array()
+        .expect("an array of handlers");
+
+    for (_, event, handler) in handlers.iter() {
+        // Handlers are resolved once, then fired as often as needed.
+        let handler = Callable::new(handler)?;
+
+        for _ in 0..2 {
+            println!("{} -> {}", event, handler.call(&["world"])?);
+        }
+    }
+
+    let strlen = context.callable("strlen")?;
+    dbg!(strlen.name(), strlen.call(&["Hello, world!"])?);
+
+    // Instance methods need an object to be called on.
+    if let Err(error) = context.callable("Greeter::greet") {
+        println!("{}", error);
+    }
+
+    Ok(())
+}
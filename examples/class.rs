@@ -0,0 +1,58 @@
+use libphp::{
+    exec::{ClassBuilder, Context, Signature, Type},
+    Error,
+};
+
+struct Counter {
+    count: i64,
+    step: i64,
+}
+
+fn main() -> Result<(), Error> {
+    let mut context = Context::new();
+
+    let counter = ClassBuilder::<Counter>::new("Counter")
+        .constructor(|step: Option<i64>| -> Result<Counter, String> {
+            Ok(Counter {
+                count: 0,
+                step: step.unwrap_or(1),
+            })
+        })
+        .method("increment", |this: &mut Counter| -> Result<i64, String> {
+            this.count += this.step;
+            Ok(this.count)
+        })
+        // Countable::count() declares an int return type, which the implementation must match.
+        .method_with_signature(
+            "count",
+            Signature::new().returns(Type::Int),
+            |this: &mut Counter| -> Result<i64, String> { Ok(this.count) },
+        )
+        .static_method("startingAt", |count: i64| -> Result<String, String> {
+            Ok(format!("Counters start at 0, not {}.", count))
+        })
+        .property("label", "counter")
+        .constant("DEFAULT_STEP", 1_i64)
+        .implements("Countable")
+        .final_class();
+
+    context.register_class(counter)?;
+
+    dbg!(context.result_of(
+        "(function () {
+            $counter = new Counter(2);
+            $counter->increment();
+            $counter->increment();
+
+            return count($counter);
+        })()",
+    )?);
+
+    dbg!(context.result_of("(new Counter())->increment()")?);
+    dbg!(context.result_of("Counter::DEFAULT_STEP")?);
+    dbg!(context.result_of("(new Counter())->label")?);
+    dbg!(context.result_of("Counter::startingAt(5)")?);
+    dbg!(context.result_of("(new Counter()) instanceof Countable")?);
+
+    Ok(())
+}
@@ -0,0 +1,42 @@
+use libphp::{
+    exec::Context,
+    value::{array::Array, Value},
+    Error,
+};
+
+fn main() -> Result<(), Error> {
+    let mut context = Context::new();
+
+    // The rule is parsed and compiled once...
+    let rule = context.compile_file("./examples/scripts/rule.php")?;
+
+    // ...and then executed for every order.
+    for (total, tags) in [(50_i64, vec![]), (120, vec!["vip"]), (80, vec!["vip"])] {
+        let mut order = Array::new();
+        order.insert("total", total);
+        order.insert("tags", Array::from_iter(tags));
+
+        context.set_global("order", order)?;
+        dbg!(rule.execute()?);
+    }
+
+    let square = context.compile_string("return $n * $n;")?;
+
+    let squares = (1..=5_i64)
+        .map(|n| {
+            context.set_global("n", n)?;
+            square.execute()
+        })
+        .collect::<Result<Vec<Value>, Error>>()?;
+    dbg!(squares);
+
+    // A compiled script can't be executed once its context has been closed.
+    drop(context);
+
+    match square.execute() {
+        Err(Error::ContextClosed) => println!("The context has been closed."),
+        other => println!("Unexpected result: {:?}", other),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,36 @@
+use libphp::{exec::Context, value::Value, Error};
+
+fn main() -> Result<(), Error> {
+    let mut context = Context::new();
+    context.init()?;
+
+    let value = context.result_of("'42 apples'")?;
+
+    // Checked conversions fail when the value is not of the expected type...
+    if let Err(error) = i64::try_from(&value) {
+        println!("{}", error);
+    }
+
+    // ...while coercion follows PHP's cast rules.
+    dbg!(value.to_int(), value.to_float(), value.to_bool());
+
+    let value = context.result_of("300")?;
+    dbg!(u16::try_from(&value).is_ok());
+
+    if let Err(error) = u8::try_from(&value) {
+        println!("{}", error);
+    }
+
+    let values: Vec<Value> = vec![
+        Value::from(()),
+        Value::from(true),
+        Value::from(1.5_f32),
+        Value::from(7_u32),
+        Value::from(Some("name")),
+        Value::from(b"\x00binary".to_vec()),
+    ];
+
+    dbg!(values);
+
+    Ok(())
+}
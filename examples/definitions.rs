@@ -0,0 +1,44 @@
+use libphp::{
+    exec::{Context, Origin},
+    Error,
+};
+
+fn main() -> Result<(), Error> {
+    let mut context = Context::new();
+    context.execute_file("./examples/scripts/plugin.php")?;
+
+    // Check that the plugin exports its entry points before calling them.
+    dbg!(
+        context.function_exists("Plugin\\register"),
+        context.class_exists("Plugin\\Logger"),
+        // Interfaces aren't classes.
+        context.class_exists("Plugin\\Hook"),
+    );
+
+    dbg!(
+        context.constant("Plugin\\VERSION"),
+        context.constant("Plugin\\Logger::LEVEL"),
+        context.constant("PHP_VERSION"),
+        context.constant("Plugin\\MISSING"),
+    );
+
+    for definition in context.functions()? {
+        if definition.origin == Origin::User {
+            println!("function {}", definition.name);
+        }
+    }
+
+    for definition in context.classes()? {
+        if definition.origin == Origin::User {
+            println!("class {}", definition.name);
+        }
+    }
+
+    for definition in context.constants()? {
+        if definition.origin == Origin::User {
+            println!("const {}", definition.name);
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,23 @@
+use std::{cell::RefCell, rc::Rc};
+
+use libphp::exec::{Context, Severity};
+
+fn main() {
+    let mut context = Context::new();
+    let deprecations = Rc::new(RefCell::new(Vec::new()));
+    let sink = deprecations.clone();
+
+    context.on_error(move |diagnostic| {
+        eprintln!("{}", diagnostic);
+
+        if diagnostic.severity == Severity::Deprecated {
+            sink.borrow_mut().push(diagnostic);
+        }
+    });
+
+    dbg!(context.result_of("$undefined ?? 'default'").unwrap());
+    dbg!(context.result_of("$undefined").unwrap());
+    dbg!(context.result_of("strlen(null)").unwrap());
+
+    assert_eq!(deprecations.borrow().len(), 1);
+}
@@ -0,0 +1,33 @@
+use libphp::{exec::Context, Error};
+
+fn main() -> Result<(), Error> {
+    let mut context = Context::new();
+
+    // Statements, declarations and a top-level return.
+    let total = context.eval(
+        "class Cart {
+            public array $prices = [];
+        }
+
+        $cart = new Cart();
+        $cart->prices = [4.5, 10, 2.25];
+
+        return array_sum($cart->prices);",
+    )?;
+    dbg!(total);
+
+    // A whole file from memory, with a filename of our choosing.
+    let result = context.execute_source(
+        "#!/usr/bin/env php\n<?php\n\nreturn basename(__FILE__) . ':' . __LINE__;\n",
+        "/virtual/report.php",
+    )?;
+    dbg!(result);
+
+    if let Err(error) =
+        context.execute_source("<?php\n\nthrow new Exception('oops');", "failing.php")
+    {
+        println!("{}", error);
+    }
+
+    Ok(())
+}
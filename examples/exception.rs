@@ -0,0 +1,16 @@
+use libphp::{exec::Context, Error};
+
+fn main() {
+    let mut context = Context::new();
+
+    match context.result_of("throw new RuntimeException('Something went wrong.', 42)") {
+        Err(Error::UncaughtException(exception)) => {
+            println!("Caught {} (code {}): {}", exception.class_name, exception.code, exception.message);
+            println!("{}", exception.trace);
+        }
+        other => println!("Unexpected result: {:?}", other),
+    }
+
+    // The exception has been cleared, so the context can keep running code.
+    dbg!(context.result_of("1 + 1").unwrap());
+}
@@ -1,15 +1,19 @@
-use libphp::{exec::Context, sys::{zend_execute_data, zval}};
+use libphp::{
+    exec::Context,
+    sys::{zend_execute_data, zval},
+    Error,
+};
 
-fn main() {
+fn main() -> Result<(), Error> {
     let mut context = Context::new();
 
-    context.on_init(|ctx| {
-        ctx.define_function("hello_world", hello_world);
-    });
+    context.define_function("hello_world", hello_world)?;
 
-    context.result_of("hello_world()");
+    context.result_of("hello_world()")?;
+
+    Ok(())
 }
 
-unsafe extern "C" fn hello_world(execute_data: *mut zend_execute_data, retval: *mut zval) {
+unsafe extern "C" fn hello_world(_execute_data: *mut zend_execute_data, _retval: *mut zval) {
     println!("Hello, world!");
-}
\ No newline at end of file
+}
@@ -0,0 +1,30 @@
+use libphp::{exec::Context, value::array::Array, Error};
+
+fn main() -> Result<(), Error> {
+    let mut context = Context::new();
+    context.set_global("numbers", Array::from_iter([3_i64, 1, 2]))?;
+
+    context.result_of(
+        "(function () {
+            global $numbers, $sorted, $total;
+
+            $sorted = $numbers;
+            sort($sorted);
+            $total = array_sum($numbers);
+        })()",
+    )?;
+
+    // A script can leave several results behind for the host to collect.
+    dbg!(context.get_global("sorted"), context.get_global("total"));
+
+    context.unset_global("numbers");
+    dbg!(context.get_global("numbers"));
+
+    for (name, value) in context.globals()? {
+        if !name.starts_with('_') {
+            println!("${} = {:?}", name, value);
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,19 @@
+use libphp::{exec::Context, Error};
+
+fn main() -> Result<(), Error> {
+    let mut context = Context::builder()
+        .ini("memory_limit", "256M")
+        .ini("error_reporting", "E_ALL & ~E_DEPRECATED")
+        .build()?;
+
+    println!("memory_limit = {:?}", context.ini_get("memory_limit"));
+
+    context.ini_set("precision", "5")?;
+    dbg!(context.result_of("M_PI")?);
+
+    if let Err(error) = context.ini_set("does_not_exist", "1") {
+        println!("{}", error);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,32 @@
+use libphp::{exec::Context, Error};
+
+fn main() -> Result<(), Error> {
+    let mut context = Context::new();
+
+    let snippets = [
+        "<?php\n\nfunction greet(string $name): string\n{\n    return \"Hello, {$name}!\";\n}\n",
+        "<?php\n\n$total = array_sum([1, 2, 3]\necho $total;\n",
+        "<?php\n\nclass A {}\nclass A {}\n",
+    ];
+
+    for snippet in snippets {
+        match context.lint_string(snippet)?.as_slice() {
+            [] => println!("No syntax errors detected"),
+            errors => {
+                for error in errors {
+                    println!("{}", error);
+                }
+            }
+        }
+    }
+
+    // Linting doesn't declare anything, so the same code can be checked again.
+    dbg!(
+        context.lint_string(snippets[0])?,
+        context.function_exists("greet")
+    );
+
+    dbg!(context.lint_file("./examples/scripts/functions.php")?);
+
+    Ok(())
+}
@@ -0,0 +1,28 @@
+use libphp::{exec::Context, Error};
+
+fn main() -> Result<(), Error> {
+    let mut context = Context::new();
+
+    let date = context.result_of("new DateTimeImmutable('2024-02-29 12:00:00')")?;
+    let mut date = date.to_object().expect("a DateTimeImmutable object");
+
+    dbg!(date.class_name());
+    dbg!(date.instance_of("DateTimeInterface"));
+    dbg!(date.method_exists("format"));
+    dbg!(date.call_method("format", &["l jS F Y"])?);
+
+    let point = context.result_of("new class { public int $x = 1; private int $y = 2; }")?;
+    let mut point = point.to_object().expect("an anonymous class object");
+
+    point.set_property("x", 10)?;
+    dbg!(point.get_property("x")?);
+    dbg!(point.get_property("y")?);
+    dbg!(point.has_property("z"));
+
+    // Assigning a value of the wrong type to a typed property throws a TypeError.
+    if let Err(error) = point.set_property("x", "ten") {
+        println!("{}", error);
+    }
+
+    Ok(())
+}
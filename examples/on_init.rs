@@ -1,11 +1,16 @@
+use std::env::var;
+
 use libphp::exec::Context;
 
 fn main() {
     let mut context = Context::new();
+    let user = var("USER").unwrap_or_else(|_| "unknown".to_string());
 
-    context.on_init(|ctx| {
+    context.on_init(move |ctx| {
         ctx.define("EXAMPLE_CONSTANT_FROM_RUST", "Hello, world!");
+        ctx.define("CURRENT_USER", user.as_str());
     });
 
-    dbg!(context.result_of("EXAMPLE_CONSTANT_FROM_RUST"));
-}
\ No newline at end of file
+    dbg!(context.result_of("EXAMPLE_CONSTANT_FROM_RUST").unwrap());
+    dbg!(context.result_of("CURRENT_USER").unwrap());
+}
@@ -0,0 +1,13 @@
+use libphp::exec::Context;
+
+fn main() {
+    let mut context = Context::new();
+
+    let (result, output) = context.capture(|ctx| ctx.execute_file("./examples/scripts/hello.php"));
+
+    println!("Return value: {:?}", result.unwrap());
+    println!("Captured output: {:?}", String::from_utf8_lossy(&output));
+
+    context.set_output_handler(|bytes| print!("[php] {}", String::from_utf8_lossy(bytes)));
+    context.result_of("print('Hello from a handler!' . PHP_EOL)").unwrap();
+}
@@ -0,0 +1,33 @@
+use libphp::{exec::Context, value::array::Array, Error};
+
+// Run against a debug build of PHP (--enable-debug) to have the Zend allocator report any leaked
+// or doubly freed values at shutdown.
+fn main() -> Result<(), Error> {
+    let mut context = Context::new();
+    context.init()?;
+
+    let value = context.result_of("['numbers' => [1, 2, 3], 'object' => new ArrayObject()]")?;
+
+    // Cloning adds a reference rather than copying, and each clone releases its own.
+    let clones = (0..1000).map(|_| value.clone()).collect::<Vec<_>>();
+    drop(clones);
+
+    let array = value.to_array().expect("an array");
+
+    // Borrowing a value from an array doesn't add a reference, so it can't outlive the array...
+    let numbers = array.get_ref("numbers").map(|numbers| numbers.to_value());
+    drop(array);
+
+    // ...but an owned value keeps its own reference after the array is gone.
+    dbg!(numbers);
+
+    // Clones of an array share its contents until one of them is modified.
+    let mut original = Array::from_iter([1_i64, 2, 3]);
+    let copy = original.clone();
+    original.push(4_i64);
+
+    dbg!(original.len(), copy.len());
+    dbg!(value);
+
+    Ok(())
+}
@@ -0,0 +1,24 @@
+use libphp::{exec::Context, Error};
+
+fn main() -> Result<(), Error> {
+    let greeting = String::from("Hello");
+
+    let mut context = Context::builder()
+        .register_fn("greet", move |name: String| -> Result<String, String> {
+            Ok(format!("{}, {}!", greeting, name))
+        })
+        .register_fn("checked_add", |a: i64, b: i64| {
+            a.checked_add(b).ok_or("Integer overflow.")
+        })
+        .build()?;
+
+    dbg!(context.result_of("greet('world')")?);
+    dbg!(context.result_of("checked_add(1, 2)")?);
+
+    // Errors returned from Rust are thrown as exceptions.
+    if let Err(error) = context.result_of("checked_add(PHP_INT_MAX, 1)") {
+        println!("{}", error);
+    }
+
+    Ok(())
+}
@@ -1,14 +1,17 @@
 use std::env::args;
 
-use libphp::exec::Context;
+use libphp::{exec::Context, Error};
 
-fn main() {
+fn main() -> Result<(), Error> {
     let file = args()
         .nth(1)
         .expect("Please provide the name of the script you would like to execute.");
 
-    let mut context = Context::new();
-    let return_value = context.execute_file(&file);
+    // Pass the script name and any remaining arguments through to PHP's $argv.
+    let mut context = Context::builder().args(args().skip(1)).build()?;
+    let return_value = context.execute_file(&file)?;
 
     println!("Return value of script: {:?}", return_value);
+
+    Ok(())
 }
@@ -0,0 +1,34 @@
+<?php
+
+class Greeter
+{
+    public function __construct(private string $greeting) {}
+
+    public function greet(string $name): string
+    {
+        return "{$this->greeting}, {$name}!";
+    }
+
+    public static function shout(string $name): string
+    {
+        return strtoupper($name) . '!';
+    }
+
+    public static function __callStatic(string $method, array $args): string
+    {
+        return "{$method}(" . implode(', ', $args) . ')';
+    }
+}
+
+$count = 0;
+
+return [
+    'closure' => function (string $name) use (&$count): string {
+        $count++;
+
+        return "event #{$count} for {$name}";
+    },
+    'method' => [new Greeter('Hello'), 'greet'],
+    'static' => 'Greeter::shout',
+    'magic' => 'Greeter::whisper',
+];
@@ -0,0 +1,25 @@
+<?php
+
+namespace Plugin;
+
+const VERSION = '1.2.0';
+
+interface Hook
+{
+    public function run(array $event): void;
+}
+
+class Logger implements Hook
+{
+    public const LEVEL = 'info';
+
+    public function run(array $event): void
+    {
+        echo "[" . self::LEVEL . "] " . $event['name'] . PHP_EOL;
+    }
+}
+
+function register(): array
+{
+    return [new Logger()];
+}
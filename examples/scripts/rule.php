@@ -0,0 +1,10 @@
+<?php
+
+// A pricing rule, run once per order by the host.
+$discount = $order['total'] >= 100 ? 0.1 : 0.0;
+
+if (in_array('vip', $order['tags'], true)) {
+    $discount += 0.05;
+}
+
+return round($order['total'] * (1 - $discount), 2);
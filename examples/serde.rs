@@ -0,0 +1,66 @@
+use std::collections::BTreeMap;
+
+use libphp::{
+    exec::Context,
+    value::{from_value, to_value},
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Order {
+    id: u32,
+    customer: String,
+    lines: Vec<Line>,
+    discount: Option<f64>,
+    status: Status,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Line {
+    sku: String,
+    quantity: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+enum Status {
+    Pending,
+    Shipped { tracking: String },
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut context = Context::new();
+    context.init()?;
+
+    let order = Order {
+        id: 42,
+        customer: "Ryan".to_string(),
+        lines: vec![
+            Line {
+                sku: "php-elephant".to_string(),
+                quantity: 2,
+            },
+            Line {
+                sku: "ferris".to_string(),
+                quantity: 1,
+            },
+        ],
+        discount: None,
+        status: Status::Pending,
+    };
+
+    // Rust structs become associative arrays in PHP.
+    context.bind("order", to_value(&order)?);
+    dbg!(context.result_of("json_encode($order, JSON_PRETTY_PRINT)")?);
+
+    // And PHP arrays can be read back into Rust types.
+    let shipped = context.result_of(
+        "['id' => 7, 'customer' => 'Ada', 'lines' => [], 'discount' => 0.1,
+          'status' => ['Shipped' => ['tracking' => 'XYZ123']]]",
+    )?;
+    dbg!(from_value::<Order>(&shipped)?);
+
+    let counts = context.result_of("array_count_values(['a', 'b', 'a'])")?;
+    dbg!(from_value::<BTreeMap<String, i64>>(&counts)?);
+
+    Ok(())
+}
@@ -0,0 +1,48 @@
+use libphp::{
+    exec::{Context, Param, Signature, Type},
+    Error,
+};
+
+fn main() -> Result<(), Error> {
+    let signature = Signature::new()
+        .param(Param::new("text", Type::String))
+        .param(Param::new("times", Type::Int.nullable()).default("null"))
+        .returns(Type::String);
+
+    let mut context = Context::builder()
+        .register_fn_with_signature(
+            "repeat",
+            signature,
+            |text: String, times: Option<i64>| -> Result<String, String> {
+                let times = usize::try_from(times.unwrap_or(2)).map_err(|e| e.to_string())?;
+
+                Ok(text.repeat(times))
+            },
+        )
+        // Optional arguments that are left out take their declared default.
+        .register_fn_with_signature(
+            "pad",
+            Signature::new()
+                .param(Param::new("text", Type::String))
+                .param(Param::new("width", Type::Int).default("10")),
+            |text: String, width: i64| -> Result<String, String> {
+                let width = usize::try_from(width).map_err(|e| e.to_string())?;
+
+                Ok(format!("{:>width$}", text, width = width))
+            },
+        )
+        .build()?;
+
+    // Named arguments and reflection see the declared parameter names, types and defaults.
+    dbg!(context.result_of("repeat(times: 3, text: 'ab')")?);
+    dbg!(context.result_of("repeat('ab')")?);
+    dbg!(context.result_of("(string) new ReflectionFunction('repeat')")?);
+    dbg!(context.result_of("pad('ab')")?);
+
+    // The number of arguments is checked against the signature.
+    if let Err(error) = context.result_of("repeat()") {
+        println!("{}", error);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,23 @@
+use libphp::{exec::Context, Error};
+
+fn main() -> Result<(), Error> {
+    let mut context = Context::new();
+    context.init()?;
+
+    let value = context.result_of("42")?;
+
+    // Reading a value as a string never changes it.
+    dbg!(value.as_str(), value.is_int());
+
+    // Converting produces a new string, following PHP's `(string)` cast rules.
+    dbg!(value.to_php_string()?, value.is_int());
+    println!("{}", value);
+
+    let value = context.result_of("new stdClass()")?;
+
+    if let Err(error) = value.to_php_string() {
+        println!("{}", error);
+    }
+
+    Ok(())
+}
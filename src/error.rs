@@ -0,0 +1,253 @@
+use std::{ffi::CString, fmt::Display};
+
+use crate::{
+    sys::{
+        libphp_exception_property, libphp_exception_take, libphp_exception_trace_string,
+        libphp_exit_status, libphp_last_error_lineno, libphp_last_error_message,
+        libphp_object_class_name, zval, LIBPHP_BAILOUT, LIBPHP_EXCEPTION, LIBPHP_EXIT, LIBPHP_FILE_NOT_FOUND,
+        LIBPHP_FUNCTION_NOT_FOUND, LIBPHP_METHOD_NOT_FOUND, LIBPHP_PARSE_ERROR, LIBPHP_SUCCESS,
+    },
+    value::Value,
+};
+
+/// An error produced while configuring a context or executing PHP code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// The context was given an invalid configuration. Contains a description of the problem.
+    InvalidConfiguration(String),
+    /// PHP failed to start up.
+    Startup,
+    /// The requested file could not be opened.
+    FileNotFound(String),
+    /// The code could not be parsed.
+    ParseError(ParseError),
+    /// An exception was thrown and never caught.
+    UncaughtException(PhpException),
+    /// Execution was aborted by a fatal error (zend_bailout). Contains the error's message, if any.
+    ///
+    /// Inside a Rust function called from PHP, this only tells the function to return: the PHP
+    /// code that called it is aborted too once it does, and nothing more can run until then.
+    Bailout(Option<String>),
+    /// The script called `exit()`. Contains the exit status.
+    Exit(i32),
+    /// The php.ini directive does not exist.
+    UnknownIniDirective(String),
+    /// The php.ini directive cannot be changed at runtime.
+    IniNotModifiable(String),
+    /// The php.ini directive rejected the given value.
+    InvalidIniValue { name: String, value: String },
+    /// The requested function does not exist.
+    FunctionNotFound(String),
+    /// The requested method does not exist or cannot be called. Contains `Class::method`.
+    MethodNotFound(String),
+    /// The requested class or interface does not exist.
+    ClassNotFound(String),
+    /// A class with the same name has already been declared.
+    ClassAlreadyExists(String),
+    /// A function with the same name has already been declared.
+    FunctionAlreadyExists(String),
+    /// The value cannot be called. Contains PHP's explanation.
+    NotCallable(String),
+    /// The Context the compiled script or callable belongs to has been closed.
+    ContextClosed,
+}
+
+impl Error {
+    /// Convert a status code returned by one of the wrapper's execution functions into a result.
+    ///
+    /// The `subject` is the file or function name the status relates to.
+    pub(crate) fn from_status(status: i32, subject: &str) -> Result<(), Self> {
+        match status {
+            LIBPHP_SUCCESS => Ok(()),
+            LIBPHP_FILE_NOT_FOUND => Err(Self::FileNotFound(subject.to_string())),
+            LIBPHP_PARSE_ERROR => Err(Self::ParseError(
+                PhpException::take()
+                    .map(ParseError::from)
+                    .unwrap_or_default(),
+            )),
+            LIBPHP_EXCEPTION => Err(Self::UncaughtException(
+                PhpException::take()
+                    .expect("An exception status was reported without an exception."),
+            )),
+            LIBPHP_BAILOUT => Err(Self::Bailout(last_error_message())),
+            LIBPHP_EXIT => Err(Self::Exit(unsafe { libphp_exit_status() })),
+            LIBPHP_FUNCTION_NOT_FOUND => Err(Self::FunctionNotFound(subject.to_string())),
+            LIBPHP_METHOD_NOT_FOUND => Err(Self::MethodNotFound(subject.to_string())),
+            _ => unreachable!("unknown status code {}", status),
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidConfiguration(message) => write!(f, "invalid configuration: {}", message),
+            Self::Startup => write!(f, "failed to start up PHP"),
+            Self::FileNotFound(file) => write!(f, "failed to open file \"{}\"", file),
+            Self::ParseError(error) => write!(f, "{}", error),
+            Self::UncaughtException(exception) => write!(f, "uncaught {}", exception),
+            Self::Bailout(Some(message)) => write!(f, "fatal error: {}", message),
+            Self::Bailout(None) => write!(f, "execution aborted by a fatal error"),
+            Self::Exit(status) => write!(f, "script exited with status {}", status),
+            Self::UnknownIniDirective(name) => write!(f, "unknown ini directive \"{}\"", name),
+            Self::IniNotModifiable(name) => {
+                write!(f, "ini directive \"{}\" cannot be changed at runtime", name)
+            }
+            Self::InvalidIniValue { name, value } => {
+                write!(
+                    f,
+                    "invalid value \"{}\" for ini directive \"{}\"",
+                    value, name
+                )
+            }
+            Self::FunctionNotFound(name) => write!(f, "call to undefined function {}()", name),
+            Self::MethodNotFound(name) => write!(f, "call to undefined method {}()", name),
+            Self::ClassNotFound(name) => write!(f, "class \"{}\" not found", name),
+            Self::ClassAlreadyExists(name) => write!(f, "cannot redeclare class {}", name),
+            Self::FunctionAlreadyExists(name) => write!(f, "cannot redeclare function {}()", name),
+            Self::NotCallable(reason) => write!(f, "value is not callable: {}", reason),
+            Self::ContextClosed => write!(f, "the context has been closed"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// A syntax error (or other compile-time error) in PHP code.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParseError {
+    pub message: String,
+    pub line: u32,
+    /// The column the error was found at. PHP doesn't currently report columns, so this is
+    /// always None.
+    pub column: Option<u32>,
+}
+
+impl ParseError {
+    /// Describe the fatal error that aborted compilation.
+    pub(crate) fn from_last_error() -> Self {
+        Self {
+            message: last_error_message().unwrap_or_default(),
+            line: unsafe { libphp_last_error_lineno() },
+            column: None,
+        }
+    }
+}
+
+/// Compile errors, such as `ParseError`, are thrown as exceptions.
+impl From<PhpException> for ParseError {
+    fn from(exception: PhpException) -> Self {
+        Self {
+            message: exception.message,
+            line: u32::try_from(exception.line).unwrap_or_default(),
+            column: None,
+        }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "parse error: {} on line {}", self.message, self.line)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// A PHP exception (any `Throwable`) that was thrown and not caught.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PhpException {
+    /// The name of the exception's class, e.g. `RuntimeException`.
+    pub class_name: String,
+    pub message: String,
+    /// The exception code. Non-integer codes (such as `PDOException`'s SQLSTATE) are reported as 0.
+    pub code: i64,
+    pub file: String,
+    pub line: i64,
+    /// The exception passed as `$previous` when this one was constructed.
+    pub previous: Option<Box<PhpException>>,
+    /// The formatted stack trace, as returned by `Throwable::getTraceAsString()`.
+    pub trace: String,
+}
+
+impl PhpException {
+    /// Take the current exception out of the executor, leaving it free to run more code.
+    ///
+    /// Returns `None` if no exception has been thrown.
+    pub(crate) fn take() -> Option<Self> {
+        let mut exception = zval::default();
+
+        if !unsafe { libphp_exception_take(&mut exception) } {
+            return None;
+        }
+
+        Some(Self::from_object(Value::new(&exception)))
+    }
+
+    fn from_object(mut exception: Value) -> Self {
+        let class_name =
+            read(|name| unsafe { libphp_object_class_name(exception.as_mut_ptr(), name) });
+        let trace =
+            read(|trace| unsafe { libphp_exception_trace_string(exception.as_mut_ptr(), trace) });
+        let code = property(&mut exception, "code");
+        let line = property(&mut exception, "line");
+        let previous = property(&mut exception, "previous");
+
+        Self {
+            class_name: class_name.to_string(),
+            message: property(&mut exception, "message").to_string(),
+            code: if code.is_int() { code.to_int() } else { 0 },
+            file: property(&mut exception, "file").to_string(),
+            line: if line.is_int() { line.to_int() } else { 0 },
+            previous: if previous.is_null() {
+                None
+            } else {
+                Some(Box::new(Self::from_object(previous)))
+            },
+            trace: trace.to_string(),
+        }
+    }
+}
+
+impl Display for PhpException {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {} in {}:{}",
+            self.class_name, self.message, self.file, self.line
+        )
+    }
+}
+
+impl std::error::Error for PhpException {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.previous
+            .as_deref()
+            .map(|previous| previous as &(dyn std::error::Error + 'static))
+    }
+}
+
+/// Fill a fresh zval using the given function and wrap it in a Value.
+fn read(fill: impl FnOnce(*mut zval)) -> Value {
+    let mut value = zval::default();
+    fill(&mut value);
+
+    Value::new(&value)
+}
+
+/// Read one of the properties declared by `Exception` and `Error`.
+fn property(exception: &mut Value, name: &str) -> Value {
+    let name_cstring = CString::new(name).unwrap();
+
+    read(|value| unsafe {
+        libphp_exception_property(exception.as_mut_ptr(), name_cstring.as_ptr(), value)
+    })
+}
+
+/// Get the message of the last error raised, e.g. the fatal error that caused a bailout.
+fn last_error_message() -> Option<String> {
+    let mut message = zval::default();
+    let exists = unsafe { libphp_last_error_message(&mut message) };
+    let message = Value::new(&message);
+
+    exists.then(|| message.to_string())
+}
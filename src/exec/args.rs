@@ -0,0 +1,135 @@
+use crate::{
+    sys::{libphp_zval_copy, libphp_zval_ptr_dtor, zval, HashTable},
+    value::{array::Array, Value},
+};
+
+/// The arguments for a call to PHP, built up one at a time.
+///
+/// Unlike a slice, arguments can be of different types and can be passed by name.
+#[derive(Default)]
+pub struct Args {
+    positional: Vec<Value>,
+    named: Vec<(String, Value)>,
+}
+
+impl Args {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a positional argument.
+    pub fn arg(mut self, value: impl Into<Value>) -> Self {
+        self.positional.push(value.into());
+        self
+    }
+
+    /// Add a named argument, like `name: $value` in PHP 8.
+    ///
+    /// Named arguments are passed after all of the positional ones.
+    pub fn named(mut self, name: &str, value: impl Into<Value>) -> Self {
+        self.named.push((name.to_string(), value.into()));
+        self
+    }
+
+    /// Lay the arguments out as PHP expects them, and pass them to the given call.
+    pub(crate) fn with_params(
+        &self,
+        call: impl FnOnce(u32, *mut zval, *mut HashTable) -> i32,
+    ) -> i32 {
+        // Each param holds its own reference, since PHP may turn a by-reference param into a
+        // reference that the original Value doesn't know about.
+        let mut params = self
+            .positional
+            .iter()
+            .map(|arg| unsafe {
+                let mut param = zval::default();
+                libphp_zval_copy(&mut param, arg.as_ptr() as *mut zval);
+                param
+            })
+            .collect::<Vec<zval>>();
+
+        let named = (!self.named.is_empty()).then(|| {
+            self.named
+                .iter()
+                .map(|(name, value)| (name.as_str(), value.clone()))
+                .collect::<Array>()
+        });
+
+        let status = call(
+            params.len() as u32,
+            params.as_mut_ptr(),
+            named.as_ref().map_or(std::ptr::null_mut(), Array::as_ptr),
+        );
+
+        for param in &mut params {
+            unsafe { libphp_zval_ptr_dtor(param) };
+        }
+
+        status
+    }
+}
+
+/// Something that can be passed as the arguments of a call to PHP.
+///
+/// Implemented for slices, arrays and vectors of values, tuples of up to 8 values of different
+/// types, `()` for no arguments, and `Args` for named arguments.
+pub trait IntoArgs {
+    fn into_args(self) -> Args;
+}
+
+impl IntoArgs for Args {
+    fn into_args(self) -> Args {
+        self
+    }
+}
+
+impl IntoArgs for () {
+    fn into_args(self) -> Args {
+        Args::new()
+    }
+}
+
+impl<T: Into<Value> + Clone> IntoArgs for &[T] {
+    fn into_args(self) -> Args {
+        self.iter().cloned().fold(Args::new(), Args::arg)
+    }
+}
+
+impl<T: Into<Value> + Clone, const N: usize> IntoArgs for &[T; N] {
+    fn into_args(self) -> Args {
+        self.as_slice().into_args()
+    }
+}
+
+impl<T: Into<Value>, const N: usize> IntoArgs for [T; N] {
+    fn into_args(self) -> Args {
+        self.into_iter().fold(Args::new(), Args::arg)
+    }
+}
+
+impl<T: Into<Value>> IntoArgs for Vec<T> {
+    fn into_args(self) -> Args {
+        self.into_iter().fold(Args::new(), Args::arg)
+    }
+}
+
+macro_rules! impl_into_args {
+    ($($arg:ident),*) => {
+        impl<$($arg: Into<Value>),*> IntoArgs for ($($arg,)*) {
+            fn into_args(self) -> Args {
+                let ($($arg,)*) = self;
+
+                Args::new()$(.arg($arg))*
+            }
+        }
+    };
+}
+
+impl_into_args!(A);
+impl_into_args!(A, B);
+impl_into_args!(A, B, C);
+impl_into_args!(A, B, C, D);
+impl_into_args!(A, B, C, D, E);
+impl_into_args!(A, B, C, D, E, F);
+impl_into_args!(A, B, C, D, E, F, G);
+impl_into_args!(A, B, C, D, E, F, G, H);
@@ -0,0 +1,186 @@
+use std::ffi::CString;
+
+use crate::Error;
+
+use super::{
+    ClassBuilder, Context, ErrorHandler, FunctionImplementation, OnInitCallback, OutputHandler,
+    PhpDiagnostic, PhpFunction, Signature,
+};
+
+/// A function or class to declare once the context has been initialised.
+type Registration = Box<dyn FnOnce(&mut Context) -> Result<(), Error>>;
+
+/// A builder for configuring a Context before it is initialised.
+#[derive(Default)]
+pub struct ContextBuilder {
+    argv: Vec<String>,
+    ini: Vec<(String, String)>,
+    registrations: Vec<Registration>,
+    output_handler: Option<OutputHandler>,
+    error_handler: Option<ErrorHandler>,
+    on_init: Option<OnInitCallback>,
+}
+
+impl ContextBuilder {
+    /// Create a new builder with the default configuration.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an argument to pass to the PHP context, available as `$argv`.
+    ///
+    /// The first argument is treated as the name of the script, as it is on the command line.
+    pub fn arg(mut self, arg: &str) -> Self {
+        self.argv.push(arg.to_string());
+        self
+    }
+
+    /// Add several arguments to pass to the PHP context, available as `$argv`.
+    pub fn args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.argv
+            .extend(args.into_iter().map(|arg| arg.as_ref().to_string()));
+        self
+    }
+
+    /// Set a php.ini directive.
+    ///
+    /// Directives are applied at startup, after the embed SAPI's own defaults, so this can be used
+    /// for settings that are only read during module initialisation (such as `opcache.enable_cli`).
+    /// The value is written to the INI file verbatim, so it may use constants and expressions
+    /// (`E_ALL & ~E_DEPRECATED`) and must be quoted if it contains special characters.
+    pub fn ini(mut self, name: &str, value: &str) -> Self {
+        self.ini.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Define a function in the PHP context.
+    pub fn function(self, name: &str, function: FunctionImplementation) -> Self {
+        self.function_with_signature(name, function, Signature::new())
+    }
+
+    /// Define a function in the PHP context, declaring its parameters and return type.
+    pub fn function_with_signature(
+        mut self,
+        name: &str,
+        function: FunctionImplementation,
+        signature: Signature,
+    ) -> Self {
+        let name = name.to_string();
+
+        self.registrations.push(Box::new(move |context| {
+            context.define_function_with_signature(&name, function, &signature)
+        }));
+        self
+    }
+
+    /// Define a function in the PHP context, implemented by a Rust closure.
+    ///
+    /// See [`Context::register_fn`].
+    pub fn register_fn<Args>(self, name: &str, function: impl PhpFunction<Args>) -> Self {
+        let signature = function.signature();
+        self.register_fn_with_signature(name, signature, function)
+    }
+
+    /// Define a function in the PHP context, implemented by a Rust closure with the given
+    /// signature.
+    ///
+    /// See [`Context::register_fn_with_signature`].
+    pub fn register_fn_with_signature<Args>(
+        mut self,
+        name: &str,
+        signature: Signature,
+        function: impl PhpFunction<Args>,
+    ) -> Self {
+        let name = name.to_string();
+
+        self.registrations.push(Box::new(move |context| {
+            context.register_fn_with_signature(&name, signature, function)
+        }));
+        self
+    }
+
+    /// Declare a class in the PHP context, implemented in Rust.
+    ///
+    /// Classes and functions are declared in the order they were added, before the on_init
+    /// callback is called. An error declaring the class is returned by `build`.
+    pub fn register_class<T: 'static>(mut self, class: ClassBuilder<T>) -> Self {
+        self.registrations
+            .push(Box::new(move |context| context.register_class(class)));
+        self
+    }
+
+    /// Send everything PHP outputs to the given handler instead of stdout.
+    pub fn output_handler(mut self, handler: impl FnMut(&[u8]) + 'static) -> Self {
+        self.output_handler = Some(Box::new(handler));
+        self
+    }
+
+    /// Send every error, warning and notice raised by PHP to the given handler.
+    pub fn on_error(mut self, handler: impl FnMut(PhpDiagnostic) + 'static) -> Self {
+        self.error_handler = Some(Box::new(handler));
+        self
+    }
+
+    /// Register a callback to be called once the context has been initialised.
+    pub fn on_init(mut self, callback: impl FnOnce(&mut Context) + 'static) -> Self {
+        self.on_init = Some(Box::new(callback));
+        self
+    }
+
+    /// Validate the configuration, then create and initialise the Context.
+    pub fn build(self) -> Result<Context, Error> {
+        let mut context = Context::new();
+
+        context.argv = self
+            .argv
+            .into_iter()
+            .map(|arg| {
+                CString::new(arg.as_str()).map_err(|_| {
+                    Error::InvalidConfiguration(format!("argument {:?} contains a NUL byte", arg))
+                })
+            })
+            .collect::<Result<_, _>>()?;
+
+        for (name, value) in &self.ini {
+            if name.is_empty() || name.contains(['=', '\n', '\r', '\0']) {
+                return Err(Error::InvalidConfiguration(format!(
+                    "{:?} is not a valid ini directive name",
+                    name
+                )));
+            }
+
+            if value.contains(['\n', '\r', '\0']) {
+                return Err(Error::InvalidConfiguration(format!(
+                    "the value of ini directive {:?} must be a single line",
+                    name
+                )));
+            }
+        }
+
+        context.ini = self.ini;
+
+        if let Some(handler) = self.output_handler {
+            context.set_output_handler(handler);
+        }
+
+        if let Some(handler) = self.error_handler {
+            context.on_error(handler);
+        }
+
+        context.init()?;
+
+        for registration in self.registrations {
+            registration(&mut context)?;
+        }
+
+        if let Some(callback) = self.on_init {
+            callback(&mut context);
+        }
+
+        Ok(context)
+    }
+}
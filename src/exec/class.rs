@@ -0,0 +1,519 @@
+use std::{
+    any::Any,
+    cell::RefCell,
+    collections::HashMap,
+    convert::Infallible,
+    ffi::{c_void, CString},
+    marker::PhantomData,
+    panic::{catch_unwind, AssertUnwindSafe},
+    ptr::null_mut,
+    rc::Rc,
+};
+
+use crate::{
+    sys::{
+        libphp_declare_constant, libphp_declare_property, libphp_execute_data_function_name,
+        libphp_execute_data_object_data, libphp_execute_data_scope_name,
+        libphp_execute_data_set_object_data, libphp_lookup_interface, libphp_method_handler,
+        libphp_register_class, libphp_throw_error, zend_execute_data, zend_function_entry, zval,
+        ZEND_ACC_EXPLICIT_ABSTRACT_CLASS, ZEND_ACC_FINAL, ZEND_ACC_PUBLIC, ZEND_ACC_STATIC,
+    },
+    value::Value,
+    Error,
+};
+
+use super::{
+    function::{arguments, dispatch, throw, CallError, PhpFunction},
+    signature::{Signature, Type},
+};
+
+/// A Rust closure that can be called from PHP as an instance method, receiving the object's state.
+///
+/// This is implemented for closures taking `&mut T` followed by up to 8 arguments that implement
+/// FromPhp and returning `Result<R, E>`, where `R` implements IntoPhp and `E` implements Display.
+pub trait PhpMethod<T, Args>: 'static {
+    /// The PHP types of the method's arguments.
+    fn param_types(&self) -> Vec<Type>;
+
+    /// The signature PHP sees when the method is registered without an explicit one.
+    fn signature(&self) -> Signature {
+        Signature::inferred(self.param_types())
+    }
+
+    /// Decode the arguments and call the method.
+    fn call(&self, this: &mut T, args: &[Value]) -> Result<Value, CallError>;
+}
+
+/// A Rust closure that creates the state of an object when PHP calls its constructor.
+///
+/// This is implemented for closures taking up to 8 arguments that implement FromPhp and returning
+/// `Result<T, E>`, where `E` implements Display.
+pub trait PhpConstructor<T, Args>: 'static {
+    /// The PHP types of the constructor's arguments.
+    fn param_types(&self) -> Vec<Type>;
+
+    /// The signature PHP sees when the constructor is registered without an explicit one.
+    fn signature(&self) -> Signature {
+        Signature::inferred(self.param_types())
+    }
+
+    /// Decode the arguments and create the object's state.
+    fn construct(&self, args: &[Value]) -> Result<T, CallError>;
+}
+
+/// The Rust state stored inside an object.
+type State = RefCell<Box<dyn Any>>;
+
+type ErasedConstructor = Rc<dyn Fn(&[Value]) -> Result<Box<dyn Any>, CallError>>;
+type ErasedMethod = Rc<dyn Fn(&mut dyn Any, &[Value]) -> Result<Value, CallError>>;
+type ErasedStaticMethod = Rc<dyn Fn(&[Value]) -> Result<Value, CallError>>;
+
+#[derive(Clone)]
+enum Implementation {
+    Constructor(ErasedConstructor),
+    Method(ErasedMethod),
+    Static(ErasedStaticMethod),
+}
+
+/// A registered method, along with the number of arguments its signature accepts.
+#[derive(Clone)]
+struct Method {
+    implementation: Implementation,
+    min: usize,
+    max: Option<usize>,
+}
+
+struct MethodDefinition {
+    name: String,
+    signature: Signature,
+    flags: u32,
+    implementation: Implementation,
+}
+
+thread_local! {
+    /// Methods of Rust classes registered with PHP, keyed by their lowercased class and method names.
+    static METHODS: RefCell<HashMap<(String, String), Method>> = RefCell::new(HashMap::new());
+}
+
+/// A builder for a PHP class whose objects hold a `T` and whose methods are Rust closures.
+///
+/// Register the class with `Context::register_class`.
+pub struct ClassBuilder<T> {
+    name: String,
+    methods: Vec<MethodDefinition>,
+    properties: Vec<(String, Scalar)>,
+    constants: Vec<(String, Scalar)>,
+    interfaces: Vec<String>,
+    flags: u32,
+    state: PhantomData<fn() -> T>,
+}
+
+impl<T: 'static> ClassBuilder<T> {
+    /// Create a builder for a class with the given name.
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            methods: Vec::new(),
+            properties: Vec::new(),
+            constants: Vec::new(),
+            interfaces: Vec::new(),
+            flags: 0,
+            state: PhantomData,
+        }
+    }
+
+    /// Set the constructor, which creates the object's state from the arguments passed to `new`.
+    ///
+    /// Calling an instance method on an object whose constructor has not run (for example, when a
+    /// subclass overrides `__construct` without calling the parent) throws an `Error`.
+    pub fn constructor<Args>(self, constructor: impl PhpConstructor<T, Args>) -> Self {
+        let signature = constructor.signature();
+        self.constructor_with_signature(signature, constructor)
+    }
+
+    /// Set the constructor, with the given signature.
+    pub fn constructor_with_signature<Args>(
+        mut self,
+        signature: Signature,
+        constructor: impl PhpConstructor<T, Args>,
+    ) -> Self {
+        signature.assert_matches("__construct", constructor.param_types().len());
+
+        self.methods.push(MethodDefinition {
+            name: "__construct".to_string(),
+            signature,
+            flags: ZEND_ACC_PUBLIC,
+            implementation: Implementation::Constructor(Rc::new(move |args: &[Value]| {
+                constructor
+                    .construct(args)
+                    .map(|state| Box::new(state) as Box<dyn Any>)
+            })),
+        });
+        self
+    }
+
+    /// Use `T::default()` as the constructor, taking no arguments.
+    pub fn default_constructor(self) -> Self
+    where
+        T: Default,
+    {
+        self.constructor(|| Ok::<T, Infallible>(T::default()))
+    }
+
+    /// Add a public instance method.
+    pub fn method<Args>(self, name: &str, method: impl PhpMethod<T, Args>) -> Self {
+        let signature = method.signature();
+        self.method_with_signature(name, signature, method)
+    }
+
+    /// Add a public instance method, with the given signature.
+    pub fn method_with_signature<Args>(
+        mut self,
+        name: &str,
+        signature: Signature,
+        method: impl PhpMethod<T, Args>,
+    ) -> Self {
+        signature.assert_matches(name, method.param_types().len());
+
+        let class = self.name.clone();
+
+        self.methods.push(MethodDefinition {
+            name: name.to_string(),
+            signature,
+            flags: ZEND_ACC_PUBLIC,
+            implementation: Implementation::Method(Rc::new(
+                move |this: &mut dyn Any, args: &[Value]| match this.downcast_mut::<T>() {
+                    Some(this) => method.call(this, args),
+                    None => Err(CallError::Thrown(format!(
+                        "Object does not hold the state of class {}",
+                        class
+                    ))),
+                },
+            )),
+        });
+        self
+    }
+
+    /// Add a public static method.
+    pub fn static_method<Args>(self, name: &str, method: impl PhpFunction<Args>) -> Self {
+        let signature = method.signature();
+        self.static_method_with_signature(name, signature, method)
+    }
+
+    /// Add a public static method, with the given signature.
+    pub fn static_method_with_signature<Args>(
+        mut self,
+        name: &str,
+        signature: Signature,
+        method: impl PhpFunction<Args>,
+    ) -> Self {
+        signature.assert_matches(name, method.arity());
+
+        self.methods.push(MethodDefinition {
+            name: name.to_string(),
+            signature,
+            flags: ZEND_ACC_PUBLIC | ZEND_ACC_STATIC,
+            implementation: Implementation::Static(Rc::new(move |args: &[Value]| {
+                method.call(args)
+            })),
+        });
+        self
+    }
+
+    /// Declare a public property with the given default value.
+    pub fn property(mut self, name: &str, default: impl Into<Scalar>) -> Self {
+        self.properties.push((name.to_string(), default.into()));
+        self
+    }
+
+    /// Declare a class constant.
+    pub fn constant(mut self, name: &str, value: impl Into<Scalar>) -> Self {
+        self.constants.push((name.to_string(), value.into()));
+        self
+    }
+
+    /// Implement an interface, such as `Countable`. The class must provide its methods.
+    ///
+    /// The interface must already be declared when the class is registered, since it is not
+    /// autoloaded.
+    pub fn implements(mut self, interface: &str) -> Self {
+        self.interfaces.push(interface.to_string());
+        self
+    }
+
+    /// Prevent the class from being extended.
+    pub fn final_class(mut self) -> Self {
+        self.flags |= ZEND_ACC_FINAL;
+        self
+    }
+
+    /// Prevent the class from being instantiated, so that it can only be extended.
+    pub fn abstract_class(mut self) -> Self {
+        self.flags |= ZEND_ACC_EXPLICIT_ABSTRACT_CLASS;
+        self
+    }
+
+    /// Declare the class in PHP.
+    pub(crate) fn register(self) -> Result<(), Error> {
+        let mut interfaces = self
+            .interfaces
+            .iter()
+            .map(|interface| {
+                let interface_cstr = CString::new(interface.as_str()).unwrap();
+                let ce = unsafe { libphp_lookup_interface(interface_cstr.as_ptr()) };
+
+                if ce.is_null() {
+                    Err(Error::ClassNotFound(interface.clone()))
+                } else {
+                    Ok(ce)
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let names = self
+            .methods
+            .iter()
+            .map(|method| CString::new(method.name.as_str()).unwrap())
+            .collect::<Vec<_>>();
+
+        let mut entries = self
+            .methods
+            .iter()
+            .zip(&names)
+            .map(|(method, name)| zend_function_entry {
+                fname: name.as_ptr(),
+                handler: Some(libphp_method_handler),
+                arg_info: method.signature.to_arg_info(),
+                num_args: method.signature.params().len() as u32,
+                flags: method.flags,
+            })
+            .collect::<Vec<_>>();
+
+        entries.push(zend_function_entry::default());
+
+        let class_name_cstr = CString::new(self.name.as_str()).unwrap();
+        let ce = unsafe {
+            libphp_register_class(
+                class_name_cstr.as_ptr(),
+                entries.as_ptr(),
+                self.flags,
+                if interfaces.is_empty() {
+                    null_mut()
+                } else {
+                    interfaces.as_mut_ptr()
+                },
+                interfaces.len() as u32,
+            )
+        };
+
+        if ce.is_null() {
+            return Err(Error::ClassAlreadyExists(self.name));
+        }
+
+        for (name, value) in &self.properties {
+            let name_cstr = CString::new(name.as_str()).unwrap();
+
+            unsafe {
+                libphp_declare_property(ce, name_cstr.as_ptr(), value.to_value().as_ptr());
+            }
+        }
+
+        for (name, value) in &self.constants {
+            let name_cstr = CString::new(name.as_str()).unwrap();
+
+            unsafe {
+                libphp_declare_constant(ce, name_cstr.as_ptr(), value.to_value().as_ptr());
+            }
+        }
+
+        let class = self.name.to_ascii_lowercase();
+
+        METHODS.with(|methods| {
+            let mut methods = methods.borrow_mut();
+
+            for method in self.methods {
+                methods.insert(
+                    (class.clone(), method.name.to_ascii_lowercase()),
+                    Method {
+                        implementation: method.implementation,
+                        min: method.signature.required_args(),
+                        max: method.signature.max_args(),
+                    },
+                );
+            }
+        });
+
+        Ok(())
+    }
+}
+
+/// The value of a class constant or the default of a property.
+///
+/// These are stored for the lifetime of the class, so they are limited to values that PHP can copy
+/// without reference counting. They are kept as Rust values until the class is registered, so a
+/// ClassBuilder can be created before the context is initialised.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Scalar {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+}
+
+impl Scalar {
+    fn to_value(&self) -> Value {
+        match self {
+            Self::Null => Value::null(),
+            Self::Bool(b) => (*b).into(),
+            Self::Int(i) => (*i).into(),
+            Self::Float(f) => (*f).into(),
+            Self::String(s) => s.as_str().into(),
+        }
+    }
+}
+
+impl From<()> for Scalar {
+    fn from(_: ()) -> Self {
+        Self::Null
+    }
+}
+
+impl From<bool> for Scalar {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+impl From<i64> for Scalar {
+    fn from(value: i64) -> Self {
+        Self::Int(value)
+    }
+}
+
+impl From<i32> for Scalar {
+    fn from(value: i32) -> Self {
+        Self::Int(value.into())
+    }
+}
+
+impl From<f64> for Scalar {
+    fn from(value: f64) -> Self {
+        Self::Float(value)
+    }
+}
+
+impl From<&str> for Scalar {
+    fn from(value: &str) -> Self {
+        Self::String(value.to_string())
+    }
+}
+
+impl From<String> for Scalar {
+    fn from(value: String) -> Self {
+        Self::String(value)
+    }
+}
+
+/// Forget every registered method. Called when the context that registered them is closed.
+pub(crate) fn clear() {
+    METHODS.with(|methods| methods.borrow_mut().clear());
+}
+
+unsafe extern "C" fn free_state(state: *mut c_void) {
+    // The object is being freed by PHP, so a panic in T's destructor has nowhere to go.
+    let _ = catch_unwind(AssertUnwindSafe(|| {
+        drop(Box::from_raw(state as *mut State))
+    }));
+}
+
+/// The handler PHP calls (through `libphp_method_handler`) for every method of a class registered
+/// with `Context::register_class`.
+///
+/// It looks up the closure by the names of the class and method being executed, then creates the
+/// object's state (for the constructor), borrows it (for instance methods) or calls the closure
+/// directly (for static methods).
+pub(crate) unsafe extern "C" fn method_trampoline(
+    execute_data: *mut zend_execute_data,
+    retval: *mut zval,
+) {
+    let mut class = zval::default();
+    let mut method = zval::default();
+    libphp_execute_data_scope_name(execute_data, &mut class);
+    libphp_execute_data_function_name(execute_data, &mut method);
+    let class = Value::new(&class).to_string();
+    let method = Value::new(&method).to_string();
+    let name = format!("{}::{}()", class, method);
+
+    let key = (class.to_ascii_lowercase(), method.to_ascii_lowercase());
+    let registered = METHODS.with(|methods| methods.borrow().get(&key).cloned());
+    let Some(Method {
+        implementation,
+        min,
+        max,
+    }) = registered
+    else {
+        throw(&format!("Rust method {} is not registered", name));
+        return;
+    };
+
+    let args = arguments(execute_data);
+
+    match implementation {
+        Implementation::Constructor(constructor) => {
+            dispatch(&name, &args, (min, max), retval, |args| {
+                let state = RefCell::new(constructor(args)?);
+
+                // Calling the constructor again from one of the object's methods would free the
+                // state that method is borrowing.
+                let current = libphp_execute_data_object_data(execute_data) as *const State;
+
+                if !current.is_null() && (*current).try_borrow_mut().is_err() {
+                    return Err(CallError::Thrown(format!(
+                        "{} cannot be called re-entrantly",
+                        name
+                    )));
+                }
+
+                let state = Box::into_raw(Box::new(state));
+
+                if !libphp_execute_data_set_object_data(
+                    execute_data,
+                    state as *mut c_void,
+                    Some(free_state),
+                ) {
+                    drop(Box::from_raw(state));
+                }
+
+                Ok(Value::null())
+            })
+        }
+        Implementation::Method(method) => {
+            let state = libphp_execute_data_object_data(execute_data) as *const State;
+
+            if state.is_null() {
+                let message = CString::new(format!(
+                    "Object of class {} has not been constructed",
+                    class
+                ))
+                .unwrap_or_default();
+
+                libphp_throw_error(message.as_ptr());
+                return;
+            }
+
+            dispatch(&name, &args, (min, max), retval, |args| {
+                // The method may call back into PHP, which may call another method on the same
+                // object, so the state is borrowed dynamically rather than handed out twice.
+                let mut state = (*state).try_borrow_mut().map_err(|_| {
+                    CallError::Thrown(format!("{} cannot be called re-entrantly", name))
+                })?;
+
+                method(&mut **state, args)
+            })
+        }
+        Implementation::Static(function) => {
+            dispatch(&name, &args, (min, max), retval, |args| function(args))
+        }
+    }
+}
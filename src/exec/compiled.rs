@@ -0,0 +1,78 @@
+use std::ptr::NonNull;
+
+use crate::{
+    sys::{libphp_destroy_op_array, libphp_execute_op_array, zend_op_array, zval},
+    value::Value,
+    Error,
+};
+
+use super::context::generation;
+
+/// A PHP script that has been compiled once, so that it can be executed many times without being
+/// parsed again.
+///
+/// The script belongs to the Context that compiled it. Once that Context is closed, executing the
+/// script returns `Error::ContextClosed`.
+///
+/// Functions and classes declared at the top level of the script are declared when it is
+/// compiled. Declarations PHP can only make while the script runs, such as a function declared
+/// inside an `if` block or a class extending one that isn't loaded yet, are made again on every
+/// execution, so running the script a second time aborts with a "cannot redeclare" fatal error
+/// (`Error::Bailout`). Guard such declarations with `function_exists` or `class_exists`.
+pub struct CompiledScript {
+    op_array: NonNull<zend_op_array>,
+    name: String,
+    generation: u64,
+}
+
+impl CompiledScript {
+    /// Wrap the result of compiling a script, cleaning up if compilation failed.
+    pub(super) fn new(
+        status: i32,
+        op_array: *mut zend_op_array,
+        name: &str,
+    ) -> Result<Self, Error> {
+        let script = NonNull::new(op_array).map(|op_array| Self {
+            op_array,
+            name: name.to_string(),
+            generation: generation(),
+        });
+
+        Error::from_status(status, name)?;
+
+        Ok(script.expect("A script compiled successfully without an op array."))
+    }
+
+    /// Get the filename the script was compiled from.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Execute the script and get the value of its top-level `return` statement, if any.
+    pub fn execute(&self) -> Result<Value, Error> {
+        if self.generation != generation() {
+            return Err(Error::ContextClosed);
+        }
+
+        let mut retval = zval::default();
+        let status = unsafe { libphp_execute_op_array(self.op_array.as_ptr(), &mut retval) };
+
+        let value = Value::new(&retval);
+        Error::from_status(status, &self.name)?;
+
+        Ok(value)
+    }
+}
+
+impl Drop for CompiledScript {
+    fn drop(&mut self) {
+        // The op array was freed along with the rest of the engine's memory when it shut down.
+        if self.generation != generation() {
+            return;
+        }
+
+        unsafe {
+            libphp_destroy_op_array(self.op_array.as_ptr());
+        }
+    }
+}
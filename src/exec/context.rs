@@ -1,23 +1,64 @@
-use std::{ffi::CString, ptr::{null_mut, null}};
+use std::{
+    cell::RefCell,
+    ffi::{c_char, c_void, CString},
+    ptr::null_mut,
+    rc::Rc,
+    sync::atomic::{AtomicU64, Ordering},
+};
 
 use crate::{
     sys::{
-        libphp_register_variable, php_embed_init, php_embed_shutdown, php_execute_simple_script,
-        zend_eval_string_ex, zend_file_handle, zend_stream_init_filename, zval, libphp_register_constant, zend_fcall_info, zend_call_function, libphp_zval_create_string, zend_fcall_info_cache, zend_function_entry, zend_execute_data, zend_register_functions, zend_arg_info, zend_internal_arg_info, zend_type,
+        libphp_call_function, libphp_class_exists, libphp_class_table_next, libphp_compile_file,
+        libphp_compile_string, libphp_constant_table_next, libphp_eval_string, libphp_execute_file,
+        libphp_execute_string, libphp_function_exists, libphp_function_handler,
+        libphp_function_table_next, libphp_get_constant, libphp_get_global, libphp_ini_get,
+        libphp_ini_set, libphp_lint_file, libphp_lint_string, libphp_register_constant,
+        libphp_set_error_handler, libphp_set_global, libphp_set_ini_entries,
+        libphp_set_output_handler, libphp_set_trampolines, libphp_unset_global, php_embed_init,
+        php_embed_shutdown, zend_execute_data, zend_function_entry, zend_register_functions, zval,
+        LIBPHP_INI_INVALID_VALUE, LIBPHP_INI_NOT_MODIFIABLE, LIBPHP_INI_UNKNOWN,
     },
-    value::Value,
+    value::{callable::Callable, Value},
+    Error, ParseError,
+};
+
+use super::{
+    args::IntoArgs,
+    builder::ContextBuilder,
+    class::{self, ClassBuilder},
+    compiled::CompiledScript,
+    definitions::Definitions,
+    diagnostic::{error_trampoline, ErrorHandler, PhpDiagnostic},
+    function::{self, function_trampoline, PhpFunction},
+    globals::Globals,
+    output::{output_trampoline, OutputHandler},
+    signature::Signature,
 };
 
-pub type OnInitCallback = fn(&mut Context);
+/// A callback that is run once the execution context has been initialised.
+///
+/// Closures can capture state from the host, such as configuration or a channel.
+pub type OnInitCallback = Box<dyn FnOnce(&mut Context)>;
 pub type FunctionImplementation = unsafe extern "C" fn(*mut zend_execute_data, *mut zval);
 
+/// Incremented each time PHP starts up or shuts down, so that anything holding on to memory owned
+/// by the engine can tell whether it is still the engine that allocated it.
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// The current generation of the PHP engine.
+pub(crate) fn generation() -> u64 {
+    GENERATION.load(Ordering::SeqCst)
+}
+
 #[derive(Default)]
 pub struct Context {
     initd: bool,
     on_init: Option<OnInitCallback>,
-    argc: i32,
-    argv: Vec<String>,
-    bindings: Vec<Value>,
+    pub(super) argv: Vec<CString>,
+    argv_ptrs: Vec<*mut c_char>,
+    pub(super) ini: Vec<(String, String)>,
+    output_handler: Option<Box<OutputHandler>>,
+    error_handler: Option<Box<ErrorHandler>>,
 }
 
 impl Context {
@@ -26,12 +67,19 @@ impl Context {
         Self {
             initd: false,
             on_init: None,
-            argc: 0,
             argv: Vec::new(),
-            bindings: Vec::new(),
+            argv_ptrs: Vec::new(),
+            ini: Vec::new(),
+            output_handler: None,
+            error_handler: None,
         }
     }
 
+    /// Create a builder for configuring a new PHP execution context.
+    pub fn builder() -> ContextBuilder {
+        ContextBuilder::new()
+    }
+
     /// Bind a variable to the PHP context.
     /// The variable will be available in the PHP context as a global variable.
     pub fn bind(&mut self, name: &str, value: impl Into<Value>) {
@@ -39,10 +87,95 @@ impl Context {
         let var_name_cstr = CString::new(name).unwrap();
 
         unsafe {
-            libphp_register_variable(var_name_cstr.as_ptr(), value.as_mut_ptr());
+            libphp_set_global(var_name_cstr.as_ptr(), value.as_mut_ptr());
         }
+    }
+
+    /// Get the value of a global variable, or None if it is not set.
+    pub fn get_global(&mut self, name: &str) -> Option<Value> {
+        let name_cstring = CString::new(name).unwrap();
+
+        self.init().ok()?;
+
+        let mut value = zval::default();
+        let exists = unsafe { libphp_get_global(name_cstring.as_ptr(), &mut value) };
+        let value = Value::new(&value);
+
+        exists.then_some(value)
+    }
+
+    /// Set a global variable, replacing any existing value, like `$GLOBALS[$name] = $value`.
+    pub fn set_global(&mut self, name: &str, value: impl Into<Value>) -> Result<(), Error> {
+        self.init()?;
+        self.bind(name, value);
+
+        Ok(())
+    }
+
+    /// Unset a global variable, returning false if it was not set.
+    pub fn unset_global(&mut self, name: &str) -> bool {
+        let name_cstring = CString::new(name).unwrap();
+
+        self.init().is_ok() && unsafe { libphp_unset_global(name_cstring.as_ptr()) }
+    }
+
+    /// Iterate over the global variables, e.g. to collect the results of a script.
+    pub fn globals(&mut self) -> Result<Globals<'_>, Error> {
+        self.init()?;
+
+        Ok(Globals::new(self))
+    }
+
+    /// Get the value of a constant, such as `PHP_VERSION`, `App\VERSION` or `App\Config::DEBUG`.
+    ///
+    /// Returns None if the constant is not defined. Classes are not autoloaded.
+    pub fn constant(&mut self, name: &str) -> Option<Value> {
+        let name_cstring = CString::new(name).unwrap();
+
+        self.init().ok()?;
+
+        let mut value = zval::default();
+        let exists = unsafe { libphp_get_constant(name_cstring.as_ptr(), &mut value) };
+        let value = Value::new(&value);
+
+        exists.then_some(value)
+    }
+
+    /// Check if a function is defined, like `function_exists()`.
+    pub fn function_exists(&mut self, name: &str) -> bool {
+        let name_cstring = CString::new(name).unwrap();
+
+        self.init().is_ok() && unsafe { libphp_function_exists(name_cstring.as_ptr()) }
+    }
+
+    /// Check if a class is defined, like `class_exists($name, false)`.
+    ///
+    /// Interfaces and traits are not classes, and classes are not autoloaded.
+    pub fn class_exists(&mut self, name: &str) -> bool {
+        let name_cstring = CString::new(name).unwrap();
+
+        self.init().is_ok() && unsafe { libphp_class_exists(name_cstring.as_ptr()) }
+    }
+
+    /// Iterate over the defined functions.
+    pub fn functions(&mut self) -> Result<Definitions<'_>, Error> {
+        self.init()?;
+
+        Ok(Definitions::new(self, libphp_function_table_next))
+    }
 
-        self.bindings.push(value);
+    /// Iterate over the defined classes, interfaces, traits and enums.
+    pub fn classes(&mut self) -> Result<Definitions<'_>, Error> {
+        self.init()?;
+
+        Ok(Definitions::new(self, libphp_class_table_next))
+    }
+
+    /// Iterate over the defined constants, not including class constants.
+    pub fn constants(&mut self) -> Result<Definitions<'_>, Error> {
+        self.init()?;
+
+        Ok(Definitions::new(self, libphp_constant_table_next))
     }
 
     /// Define a constant in the PHP context.
@@ -54,189 +187,523 @@ impl Context {
         unsafe {
             libphp_register_constant(constant_name_cstr.as_ptr(), value.as_mut_ptr());
         }
-
-        self.bindings.push(value);
     }
 
     /// Define a new function in the PHP context.
-    pub fn define_function(&mut self, name: &str, function: FunctionImplementation) {
-        let mut function_entry = zend_function_entry::default();   
-        let function_name_cstr = CString::new(name).unwrap();
+    pub fn define_function(
+        &mut self,
+        name: &str,
+        function: FunctionImplementation,
+    ) -> Result<(), Error> {
+        self.define_function_with_signature(name, function, &Signature::new())
+    }
 
-        let mut args: Vec<zend_internal_arg_info> = Vec::new();
+    /// Define a new function in the PHP context, declaring its parameters and return type.
+    ///
+    /// The signature is what reflection, named arguments and error messages see. PHP does not
+    /// check the arguments passed to the function against it, so the implementation must.
+    pub fn define_function_with_signature(
+        &mut self,
+        name: &str,
+        function: FunctionImplementation,
+        signature: &Signature,
+    ) -> Result<(), Error> {
+        self.declare_function(name, signature, || {
+            function::register_handler(name, function)
+        })
+    }
 
-        let mut arg = zend_internal_arg_info::default();
-        arg.name = null();
+    /// Declare a function in the PHP context, after storing its implementation under its name with
+    /// `register` so that PHP calls it through the trampoline.
+    fn declare_function(
+        &mut self,
+        name: &str,
+        signature: &Signature,
+        register: impl FnOnce(),
+    ) -> Result<(), Error> {
+        self.init()?;
+
+        // Registering the implementation first would replace the one PHP already calls.
+        if self.function_exists(name) {
+            return Err(Error::FunctionAlreadyExists(name.to_string()));
+        }
 
-        let arg_type = zend_type::default();
-        arg.type_ = arg_type;
+        register();
 
-        args.push(arg);
+        let mut function_entry = zend_function_entry::default();
+        let function_name_cstr = CString::new(name).unwrap();
 
         function_entry.fname = function_name_cstr.as_ptr();
-        function_entry.num_args = 0;
-        function_entry.handler = Some(function);
-        function_entry.arg_info = Box::into_raw(args.into_boxed_slice()) as *const zend_internal_arg_info;
-        
-        let mut functions = Vec::new();
-        functions.push(function_entry);
-        
-        unsafe {
-            zend_register_functions(null_mut(), functions.as_mut_ptr(), null_mut(), 0);
+        function_entry.num_args = signature.params().len() as u32;
+        function_entry.handler = Some(libphp_function_handler);
+        function_entry.arg_info = signature.to_arg_info();
+
+        let mut functions = vec![function_entry, zend_function_entry::default()];
+
+        let status =
+            unsafe { zend_register_functions(null_mut(), functions.as_mut_ptr(), null_mut(), 0) };
+
+        if status != 0 {
+            function::unregister(name);
+            return Err(Error::FunctionAlreadyExists(name.to_string()));
         }
+
+        Ok(())
     }
 
-    /// Specify the number of arguments to pass to the PHP context.
-    pub fn argc(&mut self, argc: i32) {
-        self.argc = argc;
+    /// Define a new function in the PHP context, implemented by a Rust closure.
+    ///
+    /// Arguments are decoded with FromPhp and the return value is encoded with IntoPhp.
+    /// Returning an `Err` throws an `Exception` with the error's message.
+    pub fn register_fn<Args>(
+        &mut self,
+        name: &str,
+        function: impl PhpFunction<Args>,
+    ) -> Result<(), Error> {
+        let signature = function.signature();
+        self.register_fn_with_signature(name, signature, function)
     }
 
-    /// Specify the arguments to pass to the PHP context.
-    pub fn argv(&mut self, argv: Vec<String>) {
-        self.argv = argv;
+    /// Define a new function in the PHP context, implemented by a Rust closure with the given
+    /// signature.
+    ///
+    /// The signature must have one parameter for each of the closure's arguments and cannot be
+    /// variadic. Optional arguments that are not passed are decoded from their declared default,
+    /// or from null if they have none.
+    pub fn register_fn_with_signature<Args>(
+        &mut self,
+        name: &str,
+        signature: Signature,
+        function: impl PhpFunction<Args>,
+    ) -> Result<(), Error> {
+        signature.assert_matches(name, function.arity());
+        self.declare_function(name, &signature, || {
+            function::register(name, &signature, function)
+        })
+    }
+
+    /// Declare a class in the PHP context, implemented in Rust.
+    pub fn register_class<T: 'static>(&mut self, class: ClassBuilder<T>) -> Result<(), Error> {
+        self.init()?;
+
+        class.register()
     }
 
     /// Execute a PHP file.
-    pub fn execute_file(&mut self, file: &str) -> Value {
-        let mut file_handle = zend_file_handle::default();
-        let cstring = CString::new(file).unwrap();
+    ///
+    /// As with `php`, the `auto_prepend_file` and `auto_append_file` directives are run before and
+    /// after it.
+    pub fn execute_file(&mut self, file: &str) -> Result<Value, Error> {
+        let file_cstring = CString::new(file).unwrap();
 
-        self.init();
+        self.init()?;
 
-        unsafe {
-            zend_stream_init_filename(&mut file_handle, cstring.as_ptr());
-        }
+        let mut retval = zval::default();
+        let status = unsafe { libphp_execute_file(file_cstring.as_ptr(), &mut retval) };
 
-        let mut retval_ptr = zval::default();
+        let value = Value::new(&retval);
+        Error::from_status(status, file)?;
 
-        unsafe {
-            php_execute_simple_script(&mut file_handle, &mut retval_ptr);
-        }
+        Ok(value)
+    }
+
+    /// Compile a PHP file without executing it, so that it can be executed many times.
+    pub fn compile_file(&mut self, file: &str) -> Result<CompiledScript, Error> {
+        let file_cstring = CString::new(file).unwrap();
+
+        self.init()?;
+
+        let mut op_array = null_mut();
+        let status = unsafe { libphp_compile_file(file_cstring.as_ptr(), &mut op_array) };
+
+        CompiledScript::new(status, op_array, file)
+    }
+
+    /// Compile a list of PHP statements, as accepted by `eval`, without executing them.
+    pub fn compile_string(&mut self, code: &str) -> Result<CompiledScript, Error> {
+        let name = "compiled code";
+        let name_cstring = CString::new(name).unwrap();
+
+        self.init()?;
 
-        self.bindings.clear();
+        let mut op_array = null_mut();
+        let status = unsafe {
+            libphp_compile_string(
+                code.as_ptr() as *const c_char,
+                code.len(),
+                name_cstring.as_ptr(),
+                true,
+                &mut op_array,
+            )
+        };
 
-        Value::new(&retval_ptr)
+        CompiledScript::new(status, op_array, name)
+    }
+
+    /// Check a PHP file for syntax errors without executing it, like `php -l`.
+    ///
+    /// Returns the errors found, which is empty if the file is valid.
+    pub fn lint_file(&mut self, file: &str) -> Result<Vec<ParseError>, Error> {
+        let file_cstring = CString::new(file).unwrap();
+
+        self.init()?;
+
+        let status = unsafe { libphp_lint_file(file_cstring.as_ptr()) };
+
+        lint_result(status, file)
+    }
+
+    /// Check PHP code, as a whole file starting with `<?php`, for syntax errors without
+    /// executing it.
+    pub fn lint_string(&mut self, code: &str) -> Result<Vec<ParseError>, Error> {
+        let name = "linted code";
+        let name_cstring = CString::new(name).unwrap();
+
+        self.init()?;
+
+        let status = unsafe {
+            libphp_lint_string(
+                code.as_ptr() as *const c_char,
+                code.len(),
+                name_cstring.as_ptr(),
+            )
+        };
+
+        lint_result(status, name)
+    }
+
+    /// Execute the contents of a PHP file from memory, using the given filename for `__FILE__`
+    /// and in error messages.
+    ///
+    /// As with a real file, the code should start with `<?php` and may start with a shebang line,
+    /// and the `auto_prepend_file` and `auto_append_file` directives are run around it.
+    pub fn execute_source(&mut self, code: &str, filename: &str) -> Result<Value, Error> {
+        self.execute_string(code, filename, false)
     }
 
     /// Evaluate a PHP expression and get the result.
-    pub fn result_of(&mut self, expression: &str) -> Value {
+    pub fn result_of(&mut self, expression: &str) -> Result<Value, Error> {
         let code_cstring =
             CString::new(expression).expect("Failed to convert the given code to a C string.");
 
         let script_name = CString::new("eval'd code").unwrap();
 
-        self.init();
+        self.init()?;
 
-        let mut retval_ptr = zval::default();
+        let mut retval = zval::default();
+        let status = unsafe {
+            libphp_eval_string(code_cstring.as_ptr(), script_name.as_ptr(), &mut retval)
+        };
 
-        unsafe {
-            zend_eval_string_ex(
-                code_cstring.as_ptr(),
-                &mut retval_ptr as *mut zval,
-                script_name.as_ptr(),
-                true,
-            );
-        }
+        let value = Value::new(&retval);
+        Error::from_status(status, "eval'd code")?;
+
+        Ok(value)
+    }
+
+    /// Run a list of PHP statements, without the leading `<?php`, and get the value of the
+    /// top-level `return` statement, if any.
+    pub fn eval(&mut self, code: &str) -> Result<Value, Error> {
+        self.execute_string(code, "eval'd code", true)
+    }
 
-        self.bindings.clear();
+    fn execute_string(
+        &mut self,
+        code: &str,
+        filename: &str,
+        after_open_tag: bool,
+    ) -> Result<Value, Error> {
+        let filename_cstring = CString::new(filename).unwrap();
+
+        self.init()?;
+
+        let mut retval = zval::default();
+        let status = unsafe {
+            libphp_execute_string(
+                code.as_ptr() as *const c_char,
+                code.len(),
+                filename_cstring.as_ptr(),
+                after_open_tag,
+                &mut retval,
+            )
+        };
+
+        let value = Value::new(&retval);
+        Error::from_status(status, filename)?;
+
+        Ok(value)
+    }
+
+    /// Resolve a callable by name, e.g. `strlen` or `Foo::bar`, to call it repeatedly.
+    pub fn callable(&mut self, name: &str) -> Result<Callable, Error> {
+        self.init()?;
 
-        Value::new(&retval_ptr)
+        Callable::new(name)
     }
 
     /// Call a PHP function with no arguments.
-    pub fn call(&mut self, name: &str) -> Value {
+    pub fn call(&mut self, name: &str) -> Result<Value, Error> {
+        self.call_with(name, ())
+    }
+
+    /// Call a PHP function with the given arguments.
+    ///
+    /// Arguments can be a slice of values, a tuple of values of different types, or `Args` to
+    /// pass arguments by name. Pass `()` for no arguments.
+    pub fn call_with(&mut self, name: &str, args: impl IntoArgs) -> Result<Value, Error> {
         let name_cstring = CString::new(name).unwrap();
 
-        self.init();
+        self.init()?;
 
-        let mut retval_ptr = zval::default();
-        
-        let mut fcall = zend_fcall_info::default();
-        let mut fcall_cache = zend_fcall_info_cache::default();
+        let args = args.into_args();
+        let mut retval = zval::default();
 
-        unsafe { libphp_zval_create_string(&mut fcall.function_name, name_cstring.as_ptr()); }
+        let status = args.with_params(|param_count, params, named_params| unsafe {
+            libphp_call_function(
+                name_cstring.as_ptr(),
+                &mut retval,
+                param_count,
+                params,
+                named_params,
+            )
+        });
 
-        fcall.param_count = 0;
-        fcall.object = null_mut();
-        fcall.size = std::mem::size_of::<zend_fcall_info>();
-        fcall.retval = &mut retval_ptr;
+        let value = Value::new(&retval);
+        Error::from_status(status, name)?;
 
-        unsafe {
-            zend_call_function(&mut fcall, &mut fcall_cache);
+        Ok(value)
+    }
+
+    /// Send everything PHP outputs to the given handler instead of stdout.
+    pub fn set_output_handler(&mut self, handler: impl FnMut(&[u8]) + 'static) {
+        self.output_handler = Some(Box::new(Box::new(handler)));
+
+        if self.initd {
+            self.install_output_handler();
         }
+    }
 
-        return Value::new(&retval_ptr);
+    /// Remove the output handler, sending output back to stdout.
+    pub fn clear_output_handler(&mut self) {
+        self.output_handler = None;
+
+        if self.initd {
+            self.install_output_handler();
+        }
     }
 
-    /// Call a PHP function with no arguments.
-    pub fn call_with(&mut self, name: &str, args: &[impl Into<Value> + Clone]) -> Value {
-        let name_cstring = CString::new(name).unwrap();
+    /// Run the given closure, collecting everything PHP outputs while it runs.
+    ///
+    /// Any previously registered output handler is restored afterwards.
+    pub fn capture<T>(&mut self, f: impl FnOnce(&mut Context) -> T) -> (T, Vec<u8>) {
+        let buffer = Rc::new(RefCell::new(Vec::new()));
+        let sink = buffer.clone();
+
+        let previous = self.output_handler.take();
+        self.set_output_handler(move |bytes| sink.borrow_mut().extend_from_slice(bytes));
+
+        let result = f(self);
 
-        self.init();
+        self.output_handler = previous;
 
-        // Convert the given arguments into a list of values.
-        let mut args = args.iter().map(|arg| arg.clone().into()).collect::<Vec<Value>>();
-        let mut retval_ptr = zval::default();
-        let mut fcall = zend_fcall_info::default();
-        let mut fcall_cache = zend_fcall_info_cache::default();
+        if self.initd {
+            self.install_output_handler();
+        }
 
-        unsafe { libphp_zval_create_string(&mut fcall.function_name, name_cstring.as_ptr()); }
+        let output = buffer.take();
 
-        fcall.param_count = args.len() as u32;
-        fcall.params = args.first_mut().unwrap().as_mut_ptr();
-        fcall.object = null_mut();
-        fcall.size = std::mem::size_of::<zend_fcall_info>();
-        fcall.retval = &mut retval_ptr;
+        (result, output)
+    }
 
-        unsafe {
-            zend_call_function(&mut fcall, &mut fcall_cache);
+    fn install_output_handler(&mut self) {
+        match self.output_handler.as_mut() {
+            Some(handler) => unsafe {
+                libphp_set_output_handler(
+                    Some(output_trampoline),
+                    handler.as_mut() as *mut OutputHandler as *mut c_void,
+                );
+            },
+            None => unsafe { libphp_set_output_handler(None, null_mut()) },
         }
+    }
+
+    /// Send every error, warning and notice raised by PHP to the given handler.
+    ///
+    /// Diagnostics passed to the handler are no longer displayed or logged by PHP itself.
+    /// Fatal errors still abort execution after the handler has been called. Diagnostics that PHP
+    /// would not report either, because they are silenced with `@` or excluded by the
+    /// `error_reporting` setting, are not passed to the handler.
+    pub fn on_error(&mut self, handler: impl FnMut(PhpDiagnostic) + 'static) {
+        self.error_handler = Some(Box::new(Box::new(handler)));
+
+        if self.initd {
+            self.install_error_handler();
+        }
+    }
 
-        return Value::new(&retval_ptr);
+    /// Forward every error, warning and notice raised by PHP to the `log` crate.
+    #[cfg(feature = "log")]
+    pub fn log_errors(&mut self) {
+        self.on_error(super::log_diagnostic);
+    }
+
+    fn install_error_handler(&mut self) {
+        match self.error_handler.as_mut() {
+            Some(handler) => unsafe {
+                libphp_set_error_handler(
+                    Some(error_trampoline),
+                    handler.as_mut() as *mut ErrorHandler as *mut c_void,
+                );
+            },
+            None => unsafe { libphp_set_error_handler(None, null_mut()) },
+        }
+    }
+
+    /// Change the value of a php.ini directive at runtime, in the same way as `ini_set()`.
+    pub fn ini_set(&mut self, name: &str, value: &str) -> Result<(), Error> {
+        let name_cstring = CString::new(name).unwrap();
+        let value_cstring = CString::new(value).unwrap();
+
+        self.init()?;
+
+        match unsafe { libphp_ini_set(name_cstring.as_ptr(), value_cstring.as_ptr()) } {
+            LIBPHP_INI_UNKNOWN => Err(Error::UnknownIniDirective(name.to_string())),
+            LIBPHP_INI_NOT_MODIFIABLE => Err(Error::IniNotModifiable(name.to_string())),
+            LIBPHP_INI_INVALID_VALUE => Err(Error::InvalidIniValue {
+                name: name.to_string(),
+                value: value.to_string(),
+            }),
+            status => Error::from_status(status, name),
+        }
+    }
+
+    /// Get the current value of a php.ini directive, or None if the directive does not exist.
+    pub fn ini_get(&mut self, name: &str) -> Option<String> {
+        let name_cstring = CString::new(name).unwrap();
+
+        self.init().ok()?;
+
+        let mut value = zval::default();
+        let exists = unsafe { libphp_ini_get(name_cstring.as_ptr(), &mut value) };
+        let value = Value::new(&value);
+
+        exists.then(|| value.to_string())
     }
 
     /// Register a callback to be called when the execution context is initialised.
-    pub fn on_init(&mut self, callback: OnInitCallback) {
-        self.on_init = Some(callback);
+    ///
+    /// If the context has already been initialised, the callback is called immediately.
+    pub fn on_init(&mut self, callback: impl FnOnce(&mut Context) + 'static) {
+        if self.initd {
+            callback(self);
+        } else {
+            self.on_init = Some(Box::new(callback));
+        }
     }
 
     /// Initialise the execution context.
     ///
     /// NOTE: This method does not need to be called manually.
-    pub fn init(&mut self) {
+    pub fn init(&mut self) -> Result<(), Error> {
         if self.initd {
-            return;
+            return Ok(());
         }
 
-        unsafe {
+        let ini_entries = self
+            .ini
+            .iter()
+            .map(|(name, value)| format!("{}={}\n", name, value))
+            .collect::<String>();
+        let ini_entries_cstring = CString::new(ini_entries).unwrap();
+
+        // PHP keeps hold of argv for the lifetime of the request, so the pointers are stored on
+        // the context rather than in a temporary.
+        self.argv_ptrs = self
+            .argv
+            .iter()
+            .map(|arg| arg.as_ptr() as *mut c_char)
+            .collect();
+
+        let status = unsafe {
+            libphp_set_ini_entries(ini_entries_cstring.as_ptr());
             php_embed_init(
-                self.argc,
-                if self.argv.is_empty() {
+                self.argv_ptrs.len() as i32,
+                if self.argv_ptrs.is_empty() {
                     null_mut()
                 } else {
-                    self.argv
-                        .iter_mut()
-                        .map(|arg| arg.as_ptr() as *mut i8)
-                        .collect::<Vec<*mut i8>>()
-                        .as_mut_ptr()
+                    self.argv_ptrs.as_mut_ptr()
                 },
-            );
+            )
+        };
+
+        if status != 0 {
+            return Err(Error::Startup);
         }
 
-        if let Some(callback) = self.on_init {
+        GENERATION.fetch_add(1, Ordering::SeqCst);
+
+        // Mark the context as initialised before running any callbacks, so that
+        // they can execute code without recursively initialising it again.
+        self.initd = true;
+
+        unsafe {
+            libphp_set_trampolines(Some(function_trampoline), Some(class::method_trampoline));
+        }
+
+        if self.output_handler.is_some() {
+            self.install_output_handler();
+        }
+
+        if self.error_handler.is_some() {
+            self.install_error_handler();
+        }
+
+        if let Some(callback) = self.on_init.take() {
             callback(self);
         }
 
-        self.initd = true;
+        Ok(())
     }
 
     /// Close the execution context.
     ///
+    /// Closing a context that isn't initialised does nothing, and a closed context is initialised
+    /// again the next time it is used.
+    ///
     /// NOTE: This method does not need to be called manually. The execution context is automatically closed when Context is dropped.
-    pub fn close(&self) {
-        if self.initd {
-            unsafe { php_embed_shutdown() };
+    pub fn close(&mut self) {
+        if !self.initd {
+            return;
+        }
+
+        self.initd = false;
+
+        unsafe {
+            php_embed_shutdown();
+            GENERATION.fetch_add(1, Ordering::SeqCst);
+            // The handlers live in this context, so PHP must stop calling them.
+            libphp_set_output_handler(None, null_mut());
+            libphp_set_error_handler(None, null_mut());
         }
+
+        // PHP no longer holds on to argv once it has shut down.
+        self.argv_ptrs.clear();
+
+        function::clear();
+        class::clear();
+    }
+}
+
+/// Turn the status of compiling code into the errors found in it.
+fn lint_result(status: i32, subject: &str) -> Result<Vec<ParseError>, Error> {
+    match Error::from_status(status, subject) {
+        Ok(()) => Ok(Vec::new()),
+        Err(Error::ParseError(error)) => Ok(vec![error]),
+        // Other compile errors, such as redeclaring a class, are thrown as a CompileError...
+        Err(Error::UncaughtException(exception)) => Ok(vec![ParseError::from(exception)]),
+        // ...or raised as fatal errors.
+        Err(Error::Bailout(_)) => Ok(vec![ParseError::from_last_error()]),
+        Err(error) => Err(error),
     }
 }
 
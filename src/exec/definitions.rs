@@ -0,0 +1,58 @@
+use std::marker::PhantomData;
+
+use crate::{sys::zval, value::Value};
+
+use super::Context;
+
+/// Whether a function, class or constant is built in or was defined by PHP code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Origin {
+    /// Defined by PHP itself, an extension or from Rust, e.g. with `Context::register_fn`.
+    Internal,
+    /// Defined by PHP code, e.g. a script run with `Context::execute_file`.
+    User,
+}
+
+/// The name and origin of a function, class or constant.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Definition {
+    pub name: String,
+    pub origin: Origin,
+}
+
+type NextDefinition = unsafe extern "C" fn(*mut u32, *mut zval, *mut bool) -> bool;
+
+/// An iterator over the functions, classes or constants defined in a context.
+pub struct Definitions<'a> {
+    next: NextDefinition,
+    pos: u32,
+    _marker: PhantomData<&'a mut Context>,
+}
+
+impl<'a> Definitions<'a> {
+    pub(super) fn new(_context: &'a mut Context, next: NextDefinition) -> Self {
+        Self {
+            next,
+            pos: 0,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl Iterator for Definitions<'_> {
+    type Item = Definition;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut name = zval::default();
+        let mut user = false;
+
+        if !unsafe { (self.next)(&mut self.pos, &mut name, &mut user) } {
+            return None;
+        }
+
+        Some(Definition {
+            name: Value::new(&name).to_string(),
+            origin: if user { Origin::User } else { Origin::Internal },
+        })
+    }
+}
@@ -0,0 +1,127 @@
+use std::{
+    ffi::{c_char, c_void},
+    fmt::Display,
+    panic::{catch_unwind, AssertUnwindSafe},
+};
+
+use crate::sys::{
+    E_COMPILE_ERROR, E_COMPILE_WARNING, E_CORE_ERROR, E_CORE_WARNING, E_DEPRECATED, E_ERROR,
+    E_NOTICE, E_PARSE, E_RECOVERABLE_ERROR, E_STRICT, E_USER_DEPRECATED, E_USER_ERROR,
+    E_USER_NOTICE, E_USER_WARNING, E_WARNING,
+};
+
+/// A callback that receives every error, warning and notice raised by PHP.
+pub type ErrorHandler = Box<dyn FnMut(PhpDiagnostic)>;
+
+/// How serious a PHP diagnostic is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Deprecated,
+    Notice,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    /// Get the severity of the given `E_*` error type.
+    pub fn from_error_type(error_type: i32) -> Self {
+        match error_type {
+            E_ERROR | E_PARSE | E_CORE_ERROR | E_COMPILE_ERROR | E_USER_ERROR
+            | E_RECOVERABLE_ERROR => Self::Error,
+            E_WARNING | E_CORE_WARNING | E_COMPILE_WARNING | E_USER_WARNING => Self::Warning,
+            E_NOTICE | E_USER_NOTICE | E_STRICT => Self::Notice,
+            E_DEPRECATED | E_USER_DEPRECATED => Self::Deprecated,
+            _ => Self::Error,
+        }
+    }
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Deprecated => write!(f, "Deprecated"),
+            Self::Notice => write!(f, "Notice"),
+            Self::Warning => write!(f, "Warning"),
+            Self::Error => write!(f, "Error"),
+        }
+    }
+}
+
+/// An error, warning or notice raised by PHP.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PhpDiagnostic {
+    pub severity: Severity,
+    /// The raw `E_*` constant, e.g. `E_USER_DEPRECATED`.
+    pub error_type: i32,
+    pub message: String,
+    /// The file the diagnostic was raised in, if it was raised while running code.
+    pub file: Option<String>,
+    pub line: u32,
+}
+
+impl Display for PhpDiagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.file {
+            Some(file) => write!(
+                f,
+                "PHP {}: {} in {} on line {}",
+                self.severity, self.message, file, self.line
+            ),
+            None => write!(f, "PHP {}: {}", self.severity, self.message),
+        }
+    }
+}
+
+/// Forward a diagnostic from `zend_error_cb` to the boxed ErrorHandler behind `context`.
+pub(crate) unsafe extern "C" fn error_trampoline(
+    error_type: i32,
+    message: *const c_char,
+    message_len: usize,
+    filename: *const c_char,
+    filename_len: usize,
+    line: u32,
+    context: *mut c_void,
+) {
+    let handler = &mut *(context as *mut ErrorHandler);
+    let message = std::slice::from_raw_parts(message as *const u8, message_len);
+    let file = (!filename.is_null()).then(|| {
+        String::from_utf8_lossy(std::slice::from_raw_parts(
+            filename as *const u8,
+            filename_len,
+        ))
+        .into_owned()
+    });
+
+    let diagnostic = PhpDiagnostic {
+        severity: Severity::from_error_type(error_type),
+        error_type,
+        message: String::from_utf8_lossy(message).into_owned(),
+        file,
+        line,
+    };
+
+    // A panic cannot unwind into PHP, which goes on to handle the diagnostic either way.
+    let _ = catch_unwind(AssertUnwindSafe(|| handler(diagnostic)));
+}
+
+/// Forward a diagnostic to the `log` crate, under the `php` target.
+#[cfg(feature = "log")]
+pub fn log_diagnostic(diagnostic: PhpDiagnostic) {
+    let level = match diagnostic.severity {
+        Severity::Error => log::Level::Error,
+        Severity::Warning | Severity::Deprecated => log::Level::Warn,
+        Severity::Notice => log::Level::Info,
+    };
+
+    match &diagnostic.file {
+        Some(file) => log::log!(
+            target: "php",
+            level,
+            "{} in {} on line {}",
+            diagnostic.message,
+            file,
+            diagnostic.line
+        ),
+        None => log::log!(target: "php", level, "{}", diagnostic.message),
+    }
+}
@@ -0,0 +1,332 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    ffi::CString,
+    fmt::Display,
+    panic::{catch_unwind, AssertUnwindSafe},
+    rc::Rc,
+};
+
+use crate::{
+    sys::{
+        libphp_execute_data_arg, libphp_execute_data_default_arg,
+        libphp_execute_data_function_name, libphp_execute_data_num_args,
+        libphp_throw_argument_count_error, libphp_throw_argument_type_error,
+        libphp_throw_exception, libphp_zval_copy, zend_execute_data, zval,
+    },
+    value::{FromPhp, IntoPhp, Value},
+};
+
+use super::{
+    class::{PhpConstructor, PhpMethod},
+    signature::{Signature, Type},
+    FunctionImplementation,
+};
+
+/// The reason a call to a Rust function from PHP failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CallError {
+    /// The wrong number of arguments was passed. A `max` of None means any number above `min`.
+    ArgumentCount { min: usize, max: Option<usize> },
+    /// An argument could not be decoded into the expected Rust type. The index starts at 0.
+    ArgumentType { index: usize, expected: String },
+    /// The function returned an error, which is thrown as an `Exception`.
+    Thrown(String),
+}
+
+/// A Rust closure that can be called from PHP.
+///
+/// This is implemented for closures taking up to 8 arguments that implement FromPhp and returning
+/// `Result<R, E>`, where `R` implements IntoPhp and `E` implements Display.
+pub trait PhpFunction<Args>: 'static {
+    /// The PHP types of the function's arguments.
+    fn param_types(&self) -> Vec<Type>;
+
+    /// The number of arguments the function takes.
+    fn arity(&self) -> usize {
+        self.param_types().len()
+    }
+
+    /// The signature PHP sees when the function is registered without an explicit one.
+    fn signature(&self) -> Signature {
+        Signature::inferred(self.param_types())
+    }
+
+    /// Decode the arguments and call the function.
+    ///
+    /// The number of arguments is checked against the signature before this is called.
+    fn call(&self, args: &[Value]) -> Result<Value, CallError>;
+}
+
+/// Decode an argument. Optional arguments without a declared default that were not passed are
+/// decoded from null.
+fn decode<T: FromPhp>(args: &[Value], index: usize) -> Result<T, CallError> {
+    let decoded = match args.get(index) {
+        Some(arg) => T::from_php(arg),
+        None => T::from_php(&Value::null()),
+    };
+
+    decoded.ok_or_else(|| CallError::ArgumentType {
+        index,
+        expected: T::php_type().to_string(),
+    })
+}
+
+fn thrown(error: impl Display) -> CallError {
+    CallError::Thrown(error.to_string())
+}
+
+macro_rules! impl_php_function {
+    ($(($arg:ident, $var:ident)),*) => {
+        impl<Func, Ret, Err, $($arg,)*> PhpFunction<($($arg,)*)> for Func
+        where
+            Func: Fn($($arg),*) -> Result<Ret, Err> + 'static,
+            Ret: IntoPhp,
+            Err: Display,
+            $($arg: FromPhp,)*
+        {
+            fn param_types(&self) -> Vec<Type> {
+                vec![$($arg::php_type()),*]
+            }
+
+            #[allow(unused_variables, unused_mut, unused_assignments)]
+            fn call(&self, args: &[Value]) -> Result<Value, CallError> {
+                let mut index = 0;
+                $(
+                    let $var = decode::<$arg>(args, index)?;
+                    index += 1;
+                )*
+
+                self($($var),*).map(IntoPhp::into_php).map_err(thrown)
+            }
+        }
+
+        impl<T, Func, Ret, Err, $($arg,)*> PhpMethod<T, ($($arg,)*)> for Func
+        where
+            T: 'static,
+            Func: Fn(&mut T, $($arg),*) -> Result<Ret, Err> + 'static,
+            Ret: IntoPhp,
+            Err: Display,
+            $($arg: FromPhp,)*
+        {
+            fn param_types(&self) -> Vec<Type> {
+                vec![$($arg::php_type()),*]
+            }
+
+            #[allow(unused_variables, unused_mut, unused_assignments)]
+            fn call(&self, this: &mut T, args: &[Value]) -> Result<Value, CallError> {
+                let mut index = 0;
+                $(
+                    let $var = decode::<$arg>(args, index)?;
+                    index += 1;
+                )*
+
+                self(this, $($var),*).map(IntoPhp::into_php).map_err(thrown)
+            }
+        }
+
+        impl<T, Func, Err, $($arg,)*> PhpConstructor<T, ($($arg,)*)> for Func
+        where
+            T: 'static,
+            Func: Fn($($arg),*) -> Result<T, Err> + 'static,
+            Err: Display,
+            $($arg: FromPhp,)*
+        {
+            fn param_types(&self) -> Vec<Type> {
+                vec![$($arg::php_type()),*]
+            }
+
+            #[allow(unused_variables, unused_mut, unused_assignments)]
+            fn construct(&self, args: &[Value]) -> Result<T, CallError> {
+                let mut index = 0;
+                $(
+                    let $var = decode::<$arg>(args, index)?;
+                    index += 1;
+                )*
+
+                self($($var),*).map_err(thrown)
+            }
+        }
+    };
+}
+
+impl_php_function!();
+impl_php_function!((A1, a1));
+impl_php_function!((A1, a1), (A2, a2));
+impl_php_function!((A1, a1), (A2, a2), (A3, a3));
+impl_php_function!((A1, a1), (A2, a2), (A3, a3), (A4, a4));
+impl_php_function!((A1, a1), (A2, a2), (A3, a3), (A4, a4), (A5, a5));
+impl_php_function!((A1, a1), (A2, a2), (A3, a3), (A4, a4), (A5, a5), (A6, a6));
+impl_php_function!(
+    (A1, a1),
+    (A2, a2),
+    (A3, a3),
+    (A4, a4),
+    (A5, a5),
+    (A6, a6),
+    (A7, a7)
+);
+impl_php_function!(
+    (A1, a1),
+    (A2, a2),
+    (A3, a3),
+    (A4, a4),
+    (A5, a5),
+    (A6, a6),
+    (A7, a7),
+    (A8, a8)
+);
+
+type ErasedFunction = Rc<dyn Fn(&[Value]) -> Result<Value, CallError>>;
+
+/// A registered function: a closure along with the number of arguments its signature accepts, or
+/// a raw handler passed to `Context::define_function`.
+#[derive(Clone)]
+enum Registered {
+    Closure {
+        function: ErasedFunction,
+        min: usize,
+        max: Option<usize>,
+    },
+    Handler(FunctionImplementation),
+}
+
+thread_local! {
+    /// Rust functions registered with PHP, keyed by their lowercased name.
+    static FUNCTIONS: RefCell<HashMap<String, Registered>> = RefCell::new(HashMap::new());
+}
+
+/// Store a function so that the trampoline can find it when PHP calls it by name.
+pub(crate) fn register<Args>(name: &str, signature: &Signature, function: impl PhpFunction<Args>) {
+    insert(
+        name,
+        Registered::Closure {
+            function: Rc::new(move |args: &[Value]| function.call(args)),
+            min: signature.required_args(),
+            max: signature.max_args(),
+        },
+    );
+}
+
+/// Store a raw handler, which the trampoline passes PHP's arguments to unchanged.
+pub(crate) fn register_handler(name: &str, handler: FunctionImplementation) {
+    insert(name, Registered::Handler(handler));
+}
+
+fn insert(name: &str, registered: Registered) {
+    FUNCTIONS.with(|functions| {
+        functions
+            .borrow_mut()
+            .insert(name.to_ascii_lowercase(), registered)
+    });
+}
+
+/// Forget a function whose declaration failed.
+pub(crate) fn unregister(name: &str) {
+    FUNCTIONS.with(|functions| functions.borrow_mut().remove(&name.to_ascii_lowercase()));
+}
+
+/// Forget every registered function. Called when the context that registered them is closed.
+pub(crate) fn clear() {
+    FUNCTIONS.with(|functions| functions.borrow_mut().clear());
+}
+
+/// The handler PHP calls (through `libphp_function_handler`) for every function defined from Rust.
+///
+/// It looks up the closure by the name of the function being executed, decodes the arguments,
+/// calls it and converts the result (or error) back into PHP.
+pub(crate) unsafe extern "C" fn function_trampoline(
+    execute_data: *mut zend_execute_data,
+    retval: *mut zval,
+) {
+    let mut name = zval::default();
+    libphp_execute_data_function_name(execute_data, &mut name);
+    let name = Value::new(&name);
+    let name = name.to_string().to_ascii_lowercase();
+
+    // Clone the closure out of the registry so that it can call back into PHP, which may in turn
+    // call another registered function.
+    let registered = FUNCTIONS.with(|functions| functions.borrow().get(&name).cloned());
+
+    match registered {
+        Some(Registered::Closure { function, min, max }) => dispatch(
+            &format!("{}()", name),
+            &arguments(execute_data),
+            (min, max),
+            retval,
+            |args| function(args),
+        ),
+        Some(Registered::Handler(handler)) => handler(execute_data, retval),
+        None => throw(&format!("Rust function {}() is not registered", name)),
+    }
+}
+
+/// Collect the arguments passed to the function being executed, followed by the declared
+/// defaults of any optional parameters that were left out.
+pub(super) unsafe fn arguments(execute_data: *mut zend_execute_data) -> Vec<Value> {
+    let passed = libphp_execute_data_num_args(execute_data);
+
+    let mut args = (0..passed)
+        .map(|n| {
+            let mut arg = zval::default();
+            libphp_execute_data_arg(execute_data, n, &mut arg);
+            Value::new(&arg)
+        })
+        .collect::<Vec<_>>();
+
+    // PHP fills in the defaults of parameters skipped with named arguments itself, but not those
+    // after the last argument passed.
+    for n in passed.. {
+        let mut default = zval::default();
+
+        if !libphp_execute_data_default_arg(execute_data, n, &mut default) {
+            break;
+        }
+
+        args.push(Value::new(&default));
+    }
+
+    args
+}
+
+/// Call a Rust implementation, writing its result to `retval` or throwing its error.
+///
+/// PHP does not check the number of arguments passed to internal functions, so it is checked
+/// against the `(min, max)` of the signature before the implementation decodes them.
+pub(super) unsafe fn dispatch(
+    name: &str,
+    args: &[Value],
+    (min, max): (usize, Option<usize>),
+    retval: *mut zval,
+    implementation: impl FnOnce(&[Value]) -> Result<Value, CallError>,
+) {
+    let result = if args.len() < min || max.is_some_and(|max| args.len() > max) {
+        Ok(Err(CallError::ArgumentCount { min, max }))
+    } else {
+        catch_unwind(AssertUnwindSafe(|| implementation(args)))
+    };
+
+    match result {
+        Ok(Ok(value)) => libphp_zval_copy(retval, value.as_ptr()),
+        Ok(Err(CallError::ArgumentCount { min, max })) => {
+            libphp_throw_argument_count_error(min as u32, max.map_or(u32::MAX, |max| max as u32))
+        }
+        Ok(Err(CallError::ArgumentType { index, expected })) => {
+            let expected = CString::new(expected).unwrap_or_default();
+            let null = Value::null();
+            let given = args.get(index).unwrap_or(&null);
+
+            libphp_throw_argument_type_error(index as u32 + 1, expected.as_ptr(), given.as_ptr());
+        }
+        Ok(Err(CallError::Thrown(message))) => throw(&message),
+        Err(_) => throw(&format!("Rust function {} panicked", name)),
+    }
+}
+
+pub(super) fn throw(message: &str) {
+    let message = CString::new(message.replace('\0', "")).unwrap();
+
+    unsafe {
+        libphp_throw_exception(message.as_ptr());
+    }
+}
@@ -0,0 +1,42 @@
+use std::marker::PhantomData;
+
+use crate::{
+    sys::{libphp_symbol_table_next, zval},
+    value::{Value, ValueRef},
+};
+
+use super::Context;
+
+/// An iterator over the global variables of a context, in the order they were defined.
+///
+/// Borrows the context, so that no PHP code can run and change the globals while iterating.
+pub struct Globals<'a> {
+    pos: u32,
+    _marker: PhantomData<&'a mut Context>,
+}
+
+impl<'a> Globals<'a> {
+    pub(super) fn new(_context: &'a mut Context) -> Self {
+        Self {
+            pos: 0,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a> Iterator for Globals<'a> {
+    type Item = (String, ValueRef<'a>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut name = zval::default();
+        let mut value = std::ptr::null_mut();
+
+        if !unsafe { libphp_symbol_table_next(&mut self.pos, &mut name, &mut value) } {
+            return None;
+        }
+
+        let name = Value::new(&name).to_string();
+
+        Some((name, unsafe { ValueRef::new(value) }))
+    }
+}
@@ -1,3 +1,25 @@
+mod args;
+mod builder;
+mod class;
+mod compiled;
 mod context;
+mod definitions;
+mod diagnostic;
+mod function;
+mod globals;
+mod output;
+mod signature;
 
+pub use args::{Args, IntoArgs};
+pub use builder::ContextBuilder;
+pub use class::{ClassBuilder, PhpConstructor, PhpMethod, Scalar};
+pub use compiled::CompiledScript;
 pub use context::*;
+#[cfg(feature = "log")]
+pub use diagnostic::log_diagnostic;
+pub use definitions::{Definition, Definitions, Origin};
+pub use diagnostic::{ErrorHandler, PhpDiagnostic, Severity};
+pub use function::{CallError, PhpFunction};
+pub use globals::Globals;
+pub use output::OutputHandler;
+pub use signature::{Param, Signature, Type};
@@ -0,0 +1,21 @@
+use std::{
+    ffi::{c_char, c_void},
+    panic::{catch_unwind, AssertUnwindSafe},
+};
+
+/// A callback that receives everything PHP writes to the output (`echo`, `print`, `var_dump`, ...).
+pub type OutputHandler = Box<dyn FnMut(&[u8])>;
+
+/// Forward a chunk of output from the SAPI to the boxed OutputHandler behind `context`.
+pub(crate) unsafe extern "C" fn output_trampoline(
+    str: *const c_char,
+    len: usize,
+    context: *mut c_void,
+) {
+    let handler = &mut *(context as *mut OutputHandler);
+    let output = std::slice::from_raw_parts(str as *const u8, len);
+
+    // A panic cannot unwind into PHP, and there is nothing to report it to while writing output,
+    // so the chunk is dropped.
+    let _ = catch_unwind(AssertUnwindSafe(|| handler(output)));
+}
@@ -0,0 +1,321 @@
+use std::{
+    ffi::{c_char, CString},
+    fmt::Display,
+    ptr::null,
+};
+
+use crate::sys::{
+    libphp_arg_info_init, zend_internal_arg_info, MAY_BE_ANY, MAY_BE_ARRAY, MAY_BE_BOOL,
+    MAY_BE_CALLABLE, MAY_BE_DOUBLE, MAY_BE_FALSE, MAY_BE_LONG, MAY_BE_NEVER, MAY_BE_NULL,
+    MAY_BE_OBJECT, MAY_BE_STATIC, MAY_BE_STRING, MAY_BE_TRUE, MAY_BE_VOID,
+};
+
+/// A PHP type, as used in parameter and return type declarations.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Type {
+    Mixed,
+    Null,
+    Bool,
+    False,
+    True,
+    Int,
+    Float,
+    String,
+    Array,
+    Object,
+    Callable,
+    Void,
+    Never,
+    Static,
+    /// A class or interface, e.g. `Class("DateTimeInterface".into())`.
+    Class(String),
+    /// The type or null, e.g. `?int`.
+    Nullable(Box<Type>),
+    /// Any of the given types, e.g. `int|string`.
+    Union(Vec<Type>),
+}
+
+impl Type {
+    /// Make the type nullable.
+    pub fn nullable(self) -> Self {
+        Self::Nullable(Box::new(self))
+    }
+
+    /// Get the type mask and the list of class names that make up the type.
+    fn flatten(&self, mask: &mut u32, classes: &mut Vec<String>) {
+        *mask |= match self {
+            Self::Mixed => MAY_BE_ANY,
+            Self::Null => MAY_BE_NULL,
+            Self::Bool => MAY_BE_BOOL,
+            Self::False => MAY_BE_FALSE,
+            Self::True => MAY_BE_TRUE,
+            Self::Int => MAY_BE_LONG,
+            Self::Float => MAY_BE_DOUBLE,
+            Self::String => MAY_BE_STRING,
+            Self::Array => MAY_BE_ARRAY,
+            Self::Object => MAY_BE_OBJECT,
+            Self::Callable => MAY_BE_CALLABLE,
+            Self::Void => MAY_BE_VOID,
+            Self::Never => MAY_BE_NEVER,
+            Self::Static => MAY_BE_STATIC,
+            Self::Class(name) => {
+                classes.push(name.clone());
+                0
+            }
+            Self::Nullable(inner) => {
+                inner.flatten(mask, classes);
+                MAY_BE_NULL
+            }
+            Self::Union(types) => {
+                for inner in types {
+                    inner.flatten(mask, classes);
+                }
+                0
+            }
+        };
+    }
+}
+
+impl Display for Type {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Mixed => write!(f, "mixed"),
+            Self::Null => write!(f, "null"),
+            Self::Bool => write!(f, "bool"),
+            Self::False => write!(f, "false"),
+            Self::True => write!(f, "true"),
+            Self::Int => write!(f, "int"),
+            Self::Float => write!(f, "float"),
+            Self::String => write!(f, "string"),
+            Self::Array => write!(f, "array"),
+            Self::Object => write!(f, "object"),
+            Self::Callable => write!(f, "callable"),
+            Self::Void => write!(f, "void"),
+            Self::Never => write!(f, "never"),
+            Self::Static => write!(f, "static"),
+            Self::Class(name) => write!(f, "{}", name),
+            Self::Nullable(inner) => write!(f, "?{}", inner),
+            Self::Union(types) => {
+                let types = types.iter().map(Type::to_string).collect::<Vec<_>>();
+                write!(f, "{}", types.join("|"))
+            }
+        }
+    }
+}
+
+/// A parameter in a function Signature.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Param {
+    name: String,
+    type_: Option<Type>,
+    default: Option<String>,
+    by_ref: bool,
+    variadic: bool,
+}
+
+impl Param {
+    /// Create a parameter with the given name and type.
+    pub fn new(name: &str, type_: Type) -> Self {
+        Self {
+            type_: Some(type_),
+            ..Self::untyped(name)
+        }
+    }
+
+    /// Create a parameter with the given name and no declared type.
+    pub fn untyped(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            type_: None,
+            default: None,
+            by_ref: false,
+            variadic: false,
+        }
+    }
+
+    /// Make the parameter optional, with the given PHP expression as its default value (e.g. `"null"`).
+    ///
+    /// PHP does not pass default values to functions implemented in Rust, but uses them when
+    /// named arguments skip over the parameter and shows them in reflection.
+    pub fn default(mut self, expression: &str) -> Self {
+        self.default = Some(expression.to_string());
+        self
+    }
+
+    /// Pass the argument by reference.
+    pub fn by_ref(mut self) -> Self {
+        self.by_ref = true;
+        self
+    }
+
+    /// Make the parameter variadic, collecting all remaining arguments. It must be the last parameter.
+    pub fn variadic(mut self) -> Self {
+        self.variadic = true;
+        self
+    }
+
+    fn is_optional(&self) -> bool {
+        self.default.is_some() || self.variadic
+    }
+}
+
+/// The parameters and return type of a function defined from Rust.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Signature {
+    params: Vec<Param>,
+    return_type: Option<Type>,
+    returns_ref: bool,
+}
+
+impl Signature {
+    /// Create a signature with no parameters and no declared return type.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a parameter.
+    pub fn param(mut self, param: Param) -> Self {
+        self.params.push(param);
+        self
+    }
+
+    /// Declare the return type.
+    pub fn returns(mut self, type_: Type) -> Self {
+        self.return_type = Some(type_);
+        self
+    }
+
+    /// Return by reference.
+    pub fn returns_ref(mut self) -> Self {
+        self.returns_ref = true;
+        self
+    }
+
+    /// The number of arguments that must be passed.
+    pub fn required_args(&self) -> usize {
+        self.params
+            .iter()
+            .position(Param::is_optional)
+            .unwrap_or(self.params.len())
+    }
+
+    /// The maximum number of arguments that can be passed, or None if the function is variadic.
+    pub fn max_args(&self) -> Option<usize> {
+        match self.params.last() {
+            Some(param) if param.variadic => None,
+            _ => Some(self.params.len()),
+        }
+    }
+
+    /// The signature of a Rust closure that takes arguments of the given types.
+    ///
+    /// Parameters are named `$arg1`, `$arg2` and so on. Trailing nullable (`Option`) parameters
+    /// are optional and default to null.
+    pub(crate) fn inferred(types: Vec<Type>) -> Self {
+        let required = types.len()
+            - types
+                .iter()
+                .rev()
+                .take_while(|type_| matches!(type_, Type::Nullable(_)))
+                .count();
+
+        types
+            .into_iter()
+            .enumerate()
+            .fold(Self::new(), |signature, (index, type_)| {
+                let param = Param::new(&format!("arg{}", index + 1), type_);
+
+                signature.param(if index < required {
+                    param
+                } else {
+                    param.default("null")
+                })
+            })
+    }
+
+    /// Check that the signature can describe a closure taking `arity` arguments.
+    pub(crate) fn assert_matches(&self, name: &str, arity: usize) {
+        assert_eq!(
+            self.params.len(),
+            arity,
+            "the signature of {}() does not match the number of arguments its closure takes",
+            name
+        );
+        assert!(
+            self.max_args().is_some(),
+            "a closure registered as {}() cannot be variadic",
+            name
+        );
+    }
+
+    pub(crate) fn params(&self) -> &[Param] {
+        &self.params
+    }
+
+    /// Build the `zend_internal_arg_info` array PHP expects for the function.
+    ///
+    /// The first element describes the return type and the number of required arguments, and the
+    /// rest describe each parameter. The array and its strings are leaked, as PHP holds on to them
+    /// for as long as the function is registered.
+    pub(crate) fn to_arg_info(&self) -> *const zend_internal_arg_info {
+        let mut arg_info = vec![zend_internal_arg_info::default(); self.params.len() + 1];
+
+        unsafe {
+            init_arg_info(
+                &mut arg_info[0],
+                self.required_args() as *const c_char,
+                self.return_type.as_ref(),
+                self.returns_ref,
+                false,
+                None,
+            );
+
+            for (param, info) in self.params.iter().zip(arg_info.iter_mut().skip(1)) {
+                init_arg_info(
+                    info,
+                    leak_cstring(&param.name),
+                    param.type_.as_ref(),
+                    param.by_ref,
+                    param.variadic,
+                    param.default.as_deref(),
+                );
+            }
+        }
+
+        Box::leak(arg_info.into_boxed_slice()).as_ptr()
+    }
+}
+
+unsafe fn init_arg_info(
+    info: &mut zend_internal_arg_info,
+    name: *const c_char,
+    type_: Option<&Type>,
+    by_ref: bool,
+    variadic: bool,
+    default: Option<&str>,
+) {
+    let mut mask = 0;
+    let mut classes = Vec::new();
+
+    if let Some(type_) = type_ {
+        type_.flatten(&mut mask, &mut classes);
+    }
+
+    libphp_arg_info_init(
+        info,
+        name,
+        mask,
+        if classes.is_empty() {
+            null()
+        } else {
+            leak_cstring(&classes.join("|"))
+        },
+        by_ref,
+        variadic,
+        default.map(leak_cstring).unwrap_or(null()),
+    );
+}
+
+fn leak_cstring(string: &str) -> *const c_char {
+    CString::new(string).unwrap().into_raw()
+}
@@ -2,6 +2,9 @@
 #![allow(non_camel_case_types)]
 #![allow(non_snake_case)]
 
+pub mod error;
 pub mod exec;
 pub mod sys;
 pub mod value;
+
+pub use error::{Error, ParseError, PhpException};
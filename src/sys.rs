@@ -8,28 +8,262 @@ pub const IS_LONG: u8 = 4;
 pub const IS_DOUBLE: u8 = 5;
 pub const IS_STRING: u8 = 6;
 pub const IS_ARRAY: u8 = 7;
+pub const IS_OBJECT: u8 = 8;
+pub const IS_REFERENCE: u8 = 10;
+
+// Type masks, used in argument and return type declarations.
+pub const MAY_BE_NULL: u32 = 1 << IS_NULL;
+pub const MAY_BE_FALSE: u32 = 1 << IS_FALSE;
+pub const MAY_BE_TRUE: u32 = 1 << IS_TRUE;
+pub const MAY_BE_BOOL: u32 = MAY_BE_FALSE | MAY_BE_TRUE;
+pub const MAY_BE_LONG: u32 = 1 << IS_LONG;
+pub const MAY_BE_DOUBLE: u32 = 1 << IS_DOUBLE;
+pub const MAY_BE_STRING: u32 = 1 << IS_STRING;
+pub const MAY_BE_ARRAY: u32 = 1 << IS_ARRAY;
+pub const MAY_BE_OBJECT: u32 = 1 << IS_OBJECT;
+pub const MAY_BE_RESOURCE: u32 = 1 << 9;
+pub const MAY_BE_ANY: u32 = MAY_BE_NULL
+    | MAY_BE_BOOL
+    | MAY_BE_LONG
+    | MAY_BE_DOUBLE
+    | MAY_BE_STRING
+    | MAY_BE_ARRAY
+    | MAY_BE_OBJECT
+    | MAY_BE_RESOURCE;
+pub const MAY_BE_CALLABLE: u32 = 1 << 12;
+pub const MAY_BE_VOID: u32 = 1 << 14;
+pub const MAY_BE_STATIC: u32 = 1 << 15;
+pub const MAY_BE_NEVER: u32 = 1 << 17;
+
+// Function and class flags.
+pub const ZEND_ACC_PUBLIC: u32 = 1 << 0;
+pub const ZEND_ACC_STATIC: u32 = 1 << 4;
+pub const ZEND_ACC_FINAL: u32 = 1 << 5;
+pub const ZEND_ACC_EXPLICIT_ABSTRACT_CLASS: u32 = 1 << 6;
+
+// Error types.
+pub const E_ERROR: i32 = 1 << 0;
+pub const E_WARNING: i32 = 1 << 1;
+pub const E_PARSE: i32 = 1 << 2;
+pub const E_NOTICE: i32 = 1 << 3;
+pub const E_CORE_ERROR: i32 = 1 << 4;
+pub const E_CORE_WARNING: i32 = 1 << 5;
+pub const E_COMPILE_ERROR: i32 = 1 << 6;
+pub const E_COMPILE_WARNING: i32 = 1 << 7;
+pub const E_USER_ERROR: i32 = 1 << 8;
+pub const E_USER_WARNING: i32 = 1 << 9;
+pub const E_USER_NOTICE: i32 = 1 << 10;
+pub const E_STRICT: i32 = 1 << 11;
+pub const E_RECOVERABLE_ERROR: i32 = 1 << 12;
+pub const E_DEPRECATED: i32 = 1 << 13;
+pub const E_USER_DEPRECATED: i32 = 1 << 14;
 
 // Hash table flags.
 pub const HASH_KEY_IS_STRING: i32 = 1;
 pub const HASH_KEY_IS_LONG: i32 = 2;
 pub const HASH_KEY_NON_EXISTENT: i32 = 3;
 
+// Status codes returned by the wrapper's execution functions.
+pub const LIBPHP_SUCCESS: i32 = 0;
+pub const LIBPHP_FILE_NOT_FOUND: i32 = 1;
+pub const LIBPHP_PARSE_ERROR: i32 = 2;
+pub const LIBPHP_EXCEPTION: i32 = 3;
+pub const LIBPHP_BAILOUT: i32 = 4;
+pub const LIBPHP_FUNCTION_NOT_FOUND: i32 = 5;
+pub const LIBPHP_EXIT: i32 = 6;
+pub const LIBPHP_INI_UNKNOWN: i32 = 7;
+pub const LIBPHP_INI_NOT_MODIFIABLE: i32 = 8;
+pub const LIBPHP_INI_INVALID_VALUE: i32 = 9;
+pub const LIBPHP_METHOD_NOT_FOUND: i32 = 10;
+
 // Misc. constants.
 pub const HT_MIN_SIZE: u32 = 8;
 
 #[link(name = "wrapper")]
 extern "C" {
     pub fn libphp_zval_get_type(zval: *const zval) -> u8;
-    pub fn libphp_zval_get_string(zval: *const zval) -> *const c_char;
-    pub fn libphp_var_export(zval: *const zval) -> *const c_char;
+    pub fn libphp_zval_get_long(zval: *const zval) -> i64;
+    pub fn libphp_zval_get_double(zval: *const zval) -> f64;
+    pub fn libphp_zval_is_true(zval: *const zval) -> bool;
+    pub fn libphp_zval_string_bytes(zval: *const zval, len: *mut usize) -> *const c_char;
+    pub fn libphp_zval_ptr_dtor(zval: *mut zval);
+    pub fn libphp_zval_copy(dst: *mut zval, src: *const zval);
+    pub fn libphp_var_export(zval: *const zval, result: *mut zval);
+    pub fn libphp_zval_refcount(zval: *const zval) -> u32;
+    pub fn libphp_memory_usage() -> usize;
 
     pub fn libphp_zval_create_string(zval: *mut zval, string: *const c_char) -> *const c_void;
     pub fn libphp_zval_create_long(zval: *mut zval, long: i64) -> *const c_void;
+    pub fn libphp_zval_create_null(zval: *mut zval);
+    pub fn libphp_zval_create_bool(zval: *mut zval, b: bool);
+    pub fn libphp_zval_create_double(zval: *mut zval, d: f64);
+    pub fn libphp_zval_create_stringl(zval: *mut zval, str: *const c_char, len: usize);
+    pub fn libphp_zval_create_reference(zval: *mut zval, value: *mut zval);
+    pub fn libphp_zval_create_array(zval: *mut zval, ht: *mut HashTable);
+    pub fn libphp_zval_copy_deref(dst: *mut zval, src: *const zval);
+
+    pub fn libphp_array_addref(ht: *mut HashTable);
+    pub fn libphp_array_release(ht: *mut HashTable);
+    pub fn libphp_array_separate(ht: *mut *mut HashTable);
+    pub fn libphp_array_lookup(ht: *mut HashTable, key: *const zval) -> *mut zval;
+    pub fn libphp_array_get(ht: *mut HashTable, key: *const zval, value: *mut zval) -> bool;
+    pub fn libphp_array_update(ht: *mut HashTable, key: *const zval, value: *mut zval);
+    pub fn libphp_array_push(ht: *mut HashTable, value: *mut zval) -> bool;
+    pub fn libphp_array_remove(ht: *mut HashTable, key: *const zval, removed: *mut zval) -> bool;
 
     pub fn libphp_zend_string_init(str: *const c_char) -> *mut zend_string;
+    pub fn libphp_string_create(str: *const c_char, len: usize) -> *mut zend_string;
+    pub fn libphp_string_bytes(string: *const zend_string, len: *mut usize) -> *const c_char;
+    pub fn libphp_string_addref(string: *mut zend_string);
+    pub fn libphp_string_release(string: *mut zend_string);
+    pub fn libphp_zval_create_str(zval: *mut zval, string: *mut zend_string);
+    pub fn libphp_zval_get_str(zval: *const zval) -> *mut zend_string;
 
     pub fn libphp_register_variable(key: *const c_char, value: *mut zval) -> *const c_void;
+    pub fn libphp_get_global(name: *const c_char, value: *mut zval) -> bool;
+    pub fn libphp_set_global(name: *const c_char, value: *mut zval);
+    pub fn libphp_unset_global(name: *const c_char) -> bool;
+    pub fn libphp_symbol_table_next(pos: *mut u32, name: *mut zval, value: *mut *mut zval) -> bool;
+    pub fn libphp_get_constant(name: *const c_char, value: *mut zval) -> bool;
+    pub fn libphp_function_exists(name: *const c_char) -> bool;
+    pub fn libphp_class_exists(name: *const c_char) -> bool;
+    pub fn libphp_function_table_next(pos: *mut u32, name: *mut zval, user: *mut bool) -> bool;
+    pub fn libphp_class_table_next(pos: *mut u32, name: *mut zval, user: *mut bool) -> bool;
+    pub fn libphp_constant_table_next(pos: *mut u32, name: *mut zval, user: *mut bool) -> bool;
     pub fn libphp_register_constant(name: *const c_char, value: *mut zval) -> *const c_void;
+
+    pub fn libphp_execute_file(filename: *const c_char, retval: *mut zval) -> i32;
+    pub fn libphp_eval_string(code: *const c_char, name: *const c_char, retval: *mut zval) -> i32;
+    pub fn libphp_execute_string(
+        code: *const c_char,
+        len: usize,
+        filename: *const c_char,
+        after_open_tag: bool,
+        retval: *mut zval,
+    ) -> i32;
+    pub fn libphp_compile_file(filename: *const c_char, op_array: *mut *mut zend_op_array) -> i32;
+    pub fn libphp_compile_string(
+        code: *const c_char,
+        len: usize,
+        filename: *const c_char,
+        after_open_tag: bool,
+        op_array: *mut *mut zend_op_array,
+    ) -> i32;
+    pub fn libphp_execute_op_array(op_array: *mut zend_op_array, retval: *mut zval) -> i32;
+    pub fn libphp_destroy_op_array(op_array: *mut zend_op_array);
+    pub fn libphp_lint_file(filename: *const c_char) -> i32;
+    pub fn libphp_lint_string(code: *const c_char, len: usize, filename: *const c_char) -> i32;
+    pub fn libphp_call_function(
+        name: *const c_char,
+        retval: *mut zval,
+        param_count: u32,
+        params: *mut zval,
+        named_params: *mut HashTable,
+    ) -> i32;
+
+    pub fn libphp_exit_status() -> i32;
+    pub fn libphp_last_error_message(message: *mut zval) -> bool;
+    pub fn libphp_last_error_lineno() -> u32;
+
+    pub fn libphp_exception_take(exception: *mut zval) -> bool;
+    pub fn libphp_exception_property(exception: *mut zval, name: *const c_char, value: *mut zval);
+    pub fn libphp_exception_trace_string(exception: *mut zval, trace: *mut zval);
+
+    pub fn libphp_object_class_name(object: *mut zval, name: *mut zval);
+    pub fn libphp_object_instance_of(object: *const zval, class_name: *const c_char) -> bool;
+    pub fn libphp_object_method_exists(object: *const zval, name: *const c_char) -> bool;
+    pub fn libphp_object_has_property(object: *const zval, name: *const c_char) -> bool;
+    pub fn libphp_object_read_property(object: *const zval, name: *const c_char, value: *mut zval) -> i32;
+    pub fn libphp_object_write_property(object: *const zval, name: *const c_char, value: *mut zval) -> i32;
+    pub fn libphp_call_method(
+        object: *mut zval,
+        name: *const c_char,
+        retval: *mut zval,
+        param_count: u32,
+        params: *mut zval,
+        named_params: *mut HashTable,
+    ) -> i32;
+    pub fn libphp_zval_to_string(zval: *const zval, result: *mut zval) -> i32;
+    pub fn libphp_zval_display(zval: *const zval, result: *mut zval);
+    pub fn libphp_callable_resolve(
+        callable: *mut zval,
+        fcc: *mut zend_fcall_info_cache,
+        name: *mut zval,
+        error: *mut zval,
+    ) -> i32;
+    pub fn libphp_callable_call(
+        callable: *mut zval,
+        fcc: *const zend_fcall_info_cache,
+        retval: *mut zval,
+        param_count: u32,
+        params: *mut zval,
+        named_params: *mut HashTable,
+    ) -> i32;
+
+    pub fn libphp_execute_data_function_name(execute_data: *mut zend_execute_data, name: *mut zval);
+    pub fn libphp_execute_data_num_args(execute_data: *mut zend_execute_data) -> u32;
+    pub fn libphp_execute_data_arg(execute_data: *mut zend_execute_data, n: u32, value: *mut zval);
+    pub fn libphp_execute_data_default_arg(
+        execute_data: *mut zend_execute_data,
+        n: u32,
+        value: *mut zval,
+    ) -> bool;
+
+    pub fn libphp_arg_info_init(
+        arg_info: *mut zend_internal_arg_info,
+        name: *const c_char,
+        type_mask: u32,
+        class_name: *const c_char,
+        by_ref: bool,
+        variadic: bool,
+        default_value: *const c_char,
+    );
+
+    pub fn libphp_throw_exception(message: *const c_char);
+    pub fn libphp_throw_argument_count_error(min: u32, max: u32);
+    pub fn libphp_throw_argument_type_error(arg_num: u32, expected: *const c_char, given: *const zval);
+    pub fn libphp_throw_error(message: *const c_char);
+
+    pub fn libphp_set_trampolines(
+        function: Option<unsafe extern "C" fn(*mut zend_execute_data, *mut zval)>,
+        method: Option<unsafe extern "C" fn(*mut zend_execute_data, *mut zval)>,
+    );
+    pub fn libphp_function_handler(execute_data: *mut zend_execute_data, retval: *mut zval);
+    pub fn libphp_method_handler(execute_data: *mut zend_execute_data, retval: *mut zval);
+
+    pub fn libphp_lookup_interface(name: *const c_char) -> *mut zend_class_entry;
+    pub fn libphp_register_class(
+        name: *const c_char,
+        methods: *const zend_function_entry,
+        flags: u32,
+        interfaces: *mut *mut zend_class_entry,
+        num_interfaces: u32,
+    ) -> *mut zend_class_entry;
+    pub fn libphp_declare_property(ce: *mut zend_class_entry, name: *const c_char, value: *const zval) -> bool;
+    pub fn libphp_declare_constant(ce: *mut zend_class_entry, name: *const c_char, value: *const zval) -> bool;
+    pub fn libphp_execute_data_scope_name(execute_data: *mut zend_execute_data, name: *mut zval);
+    pub fn libphp_execute_data_object_data(execute_data: *mut zend_execute_data) -> *mut c_void;
+    pub fn libphp_execute_data_set_object_data(
+        execute_data: *mut zend_execute_data,
+        data: *mut c_void,
+        free: Option<unsafe extern "C" fn(*mut c_void)>,
+    ) -> bool;
+
+    pub fn libphp_set_output_handler(
+        handler: Option<unsafe extern "C" fn(*const c_char, usize, *mut c_void)>,
+        context: *mut c_void,
+    );
+
+    pub fn libphp_set_error_handler(
+        handler: Option<
+            unsafe extern "C" fn(i32, *const c_char, usize, *const c_char, usize, u32, *mut c_void),
+        >,
+        context: *mut c_void,
+    );
+
+    pub fn libphp_set_ini_entries(entries: *const c_char);
+    pub fn libphp_ini_set(name: *const c_char, value: *const c_char) -> i32;
+    pub fn libphp_ini_get(name: *const c_char, value: *mut zval) -> bool;
 }
 
 include!(concat!(env!("OUT_DIR"), "/bindings.rs"));
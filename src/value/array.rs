@@ -1,13 +1,20 @@
 use std::{fmt::Display, ptr::NonNull};
 
 use crate::sys::{
-    HashTable, _zend_new_array, zend_array_count, zend_hash_get_current_data_ex,
-    zend_hash_get_current_key_type_ex, zend_hash_get_current_key_zval_ex,
-    zend_hash_move_forward_ex, zval, HASH_KEY_NON_EXISTENT, HT_MIN_SIZE,
+    HashTable, _zend_new_array, libphp_array_addref, libphp_array_get, libphp_array_lookup,
+    libphp_array_push, libphp_array_release, libphp_array_remove, libphp_array_separate,
+    libphp_array_update, libphp_zval_copy_deref, libphp_zval_create_array, zend_array_count,
+    zend_hash_get_current_data_ex, zend_hash_get_current_key_type_ex,
+    zend_hash_get_current_key_zval_ex, zend_hash_move_forward_ex, zval, HASH_KEY_NON_EXISTENT,
+    HT_MIN_SIZE,
 };
 
-use super::Value;
+use super::{Value, ValueRef};
 
+/// A PHP array.
+///
+/// Arrays have value semantics, as in PHP: modifying an Array that shares its contents with a PHP
+/// variable (or another Array) copies it first, leaving the other one unchanged.
 pub struct Array {
     ptr: NonNull<HashTable>,
 }
@@ -38,6 +45,110 @@ impl Array {
     pub fn iter(&self) -> ArrayIter {
         ArrayIter::new(unsafe { self.ptr.as_ref() })
     }
+
+    /// Get the value stored under the given key.
+    ///
+    /// As in PHP, a numeric string key such as `"1"` is the same as the integer key `1`.
+    pub fn get(&self, key: impl Into<ArrayKey>) -> Option<Value> {
+        let key = key.into().into_value();
+        let mut value = zval::default();
+
+        let found = unsafe { libphp_array_get(self.ptr.as_ptr(), key.as_ptr(), &mut value) };
+        let value = Value::new(&value);
+
+        found.then_some(value)
+    }
+
+    /// Borrow the value stored under the given key, without adding a reference to it.
+    pub fn get_ref(&self, key: impl Into<ArrayKey>) -> Option<ValueRef<'_>> {
+        let key = key.into().into_value();
+        let found = unsafe { libphp_array_lookup(self.ptr.as_ptr(), key.as_ptr()) };
+
+        (!found.is_null()).then(|| unsafe { ValueRef::new(found) })
+    }
+
+    /// Check if a value is stored under the given key.
+    pub fn contains_key(&self, key: impl Into<ArrayKey>) -> bool {
+        self.get_ref(key).is_some()
+    }
+
+    /// Store a value under the given key, replacing any existing value.
+    pub fn insert(&mut self, key: impl Into<ArrayKey>, value: impl Into<Value>) {
+        let key = key.into().into_value();
+        let mut value = value.into();
+
+        unsafe {
+            libphp_array_update(self.separate(), key.as_ptr(), value.as_mut_ptr());
+        }
+    }
+
+    /// Append a value, using the next integer key, like `$array[] = $value`.
+    ///
+    /// Returns false if the next key would overflow, which PHP treats as an error.
+    pub fn push(&mut self, value: impl Into<Value>) -> bool {
+        let mut value = value.into();
+
+        unsafe { libphp_array_push(self.separate(), value.as_mut_ptr()) }
+    }
+
+    /// Remove the value stored under the given key, returning it.
+    pub fn remove(&mut self, key: impl Into<ArrayKey>) -> Option<Value> {
+        let key = key.into().into_value();
+        let mut removed = zval::default();
+
+        let found = unsafe { libphp_array_remove(self.separate(), key.as_ptr(), &mut removed) };
+        let removed = Value::new(&removed);
+
+        found.then_some(removed)
+    }
+
+    /// Iterate over the keys, in order.
+    pub fn keys(&self) -> impl Iterator<Item = ArrayKey> + '_ {
+        self.iter().map(|(_, key, _)| key)
+    }
+
+    /// Iterate over the values, in order.
+    pub fn values(&self) -> impl Iterator<Item = Value> + '_ {
+        self.iter().map(|(_, _, value)| value)
+    }
+
+    /// Get the underlying HashTable, e.g. to pass it to PHP without modifying it.
+    pub(crate) fn as_ptr(&self) -> *mut HashTable {
+        self.ptr.as_ptr()
+    }
+
+    /// Get the underlying HashTable, copying it first if it is shared.
+    fn separate(&mut self) -> *mut HashTable {
+        let mut ptr = self.ptr.as_ptr();
+
+        unsafe {
+            libphp_array_separate(&mut ptr);
+            self.ptr = NonNull::new_unchecked(ptr);
+        }
+
+        ptr
+    }
+}
+
+/// Clones share the same contents until one of them is modified.
+impl Clone for Array {
+    fn clone(&self) -> Self {
+        self.ptr.as_ptr().into()
+    }
+}
+
+impl Default for Array {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for Array {
+    fn drop(&mut self) {
+        unsafe {
+            libphp_array_release(self.ptr.as_ptr());
+        }
+    }
 }
 
 pub struct ArrayIter<'a> {
@@ -56,16 +167,67 @@ impl<'a> ArrayIter<'a> {
     }
 }
 
+/// The key of an array entry.
+///
+/// PHP strings are byte strings, so string keys may contain NUL bytes or invalid UTF-8.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum ArrayKey {
     Int(i64),
-    String(String),
+    String(Vec<u8>),
+}
+
+impl ArrayKey {
+    fn into_value(self) -> Value {
+        match self {
+            Self::Int(i) => i.into(),
+            Self::String(s) => s.into(),
+        }
+    }
+
+    /// Get the key as a string slice, or None if it is an integer or not valid UTF-8.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::Int(_) => None,
+            Self::String(s) => std::str::from_utf8(s).ok(),
+        }
+    }
+}
+
+impl From<i64> for ArrayKey {
+    fn from(value: i64) -> Self {
+        Self::Int(value)
+    }
+}
+
+impl From<&str> for ArrayKey {
+    fn from(value: &str) -> Self {
+        Self::String(value.as_bytes().to_vec())
+    }
+}
+
+impl From<String> for ArrayKey {
+    fn from(value: String) -> Self {
+        Self::String(value.into_bytes())
+    }
+}
+
+impl From<&[u8]> for ArrayKey {
+    fn from(value: &[u8]) -> Self {
+        Self::String(value.to_vec())
+    }
+}
+
+impl From<Vec<u8>> for ArrayKey {
+    fn from(value: Vec<u8>) -> Self {
+        Self::String(value)
+    }
 }
 
 impl Display for ArrayKey {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::Int(i) => write!(f, "{}", i),
-            Self::String(s) => write!(f, "{}", s),
+            Self::String(s) => write!(f, "{}", String::from_utf8_lossy(s)),
         }
     }
 }
@@ -97,16 +259,27 @@ impl<'a> Iterator for ArrayIter<'a> {
 
         let key = Value::new(&key);
 
-        let value = Value::new(unsafe {
-            &*zend_hash_get_current_data_ex(
-                self.ptr as *const HashTable as *mut HashTable,
-                &mut self.pos,
+        let mut value = zval::default();
+
+        unsafe {
+            libphp_zval_copy_deref(
+                &mut value,
+                zend_hash_get_current_data_ex(
+                    self.ptr as *const HashTable as *mut HashTable,
+                    &mut self.pos,
+                ),
             )
-        });
+        };
+
+        let value = Value::new(&value);
 
         let item = match key.is_int() {
             true => (self.idx, ArrayKey::Int(key.to_int()), value),
-            false => (self.idx, ArrayKey::String(key.to_string()), value),
+            false => (
+                self.idx,
+                ArrayKey::String(key.as_bytes().unwrap_or_default().to_vec()),
+                value,
+            ),
         };
 
         unsafe {
@@ -122,10 +295,54 @@ impl<'a> Iterator for ArrayIter<'a> {
     }
 }
 
+/// Wrap an existing array, adding a reference to it.
 impl From<*mut HashTable> for Array {
     fn from(value: *mut HashTable) -> Self {
+        unsafe {
+            libphp_array_addref(value);
+        }
+
         Self {
             ptr: unsafe { NonNull::new_unchecked(value) },
         }
     }
 }
+
+impl From<Array> for Value {
+    fn from(array: Array) -> Self {
+        let mut zval = zval::default();
+
+        unsafe {
+            libphp_zval_create_array(&mut zval, array.ptr.as_ptr());
+        }
+
+        // The Value takes over the Array's reference.
+        std::mem::forget(array);
+
+        Value::new(&zval)
+    }
+}
+
+impl<V: Into<Value>> FromIterator<V> for Array {
+    fn from_iter<T: IntoIterator<Item = V>>(iter: T) -> Self {
+        let mut array = Array::new();
+
+        for value in iter {
+            array.push(value);
+        }
+
+        array
+    }
+}
+
+impl<K: Into<ArrayKey>, V: Into<Value>> FromIterator<(K, V)> for Array {
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
+        let mut array = Array::new();
+
+        for (key, value) in iter {
+            array.insert(key, value);
+        }
+
+        array
+    }
+}
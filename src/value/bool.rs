@@ -0,0 +1,15 @@
+use crate::sys::{libphp_zval_create_bool, zval};
+
+use super::Value;
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        let mut zval = zval::default();
+
+        unsafe {
+            libphp_zval_create_bool(&mut zval, value);
+        }
+
+        Self::new(&zval)
+    }
+}
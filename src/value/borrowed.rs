@@ -0,0 +1,60 @@
+use std::{
+    fmt::{Debug, Display},
+    marker::PhantomData,
+    mem::ManuallyDrop,
+    ops::Deref,
+};
+
+use crate::sys::zval;
+
+use super::Value;
+
+/// A PHP value borrowed from an array or the symbol table.
+///
+/// Reading through a ValueRef doesn't add a reference, so it can't outlive its container. Use
+/// `to_value()` to get an owned Value.
+pub struct ValueRef<'a> {
+    value: ManuallyDrop<Value>,
+    _marker: PhantomData<&'a zval>,
+}
+
+impl<'a> ValueRef<'a> {
+    /// Borrow the given zval, which must stay alive and unmodified for 'a.
+    pub(crate) unsafe fn new(zval: *const zval) -> Self {
+        Self {
+            value: ManuallyDrop::new(Value::new(&*zval)),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Get an owned Value, adding a reference to the borrowed one.
+    pub fn to_value(&self) -> Value {
+        Value::clone(self)
+    }
+}
+
+impl Deref for ValueRef<'_> {
+    type Target = Value;
+
+    fn deref(&self) -> &Value {
+        &self.value
+    }
+}
+
+impl From<ValueRef<'_>> for Value {
+    fn from(value: ValueRef<'_>) -> Self {
+        value.to_value()
+    }
+}
+
+impl Debug for ValueRef<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(&*self.value, f)
+    }
+}
+
+impl Display for ValueRef<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&*self.value, f)
+    }
+}
@@ -0,0 +1,107 @@
+use std::fmt::Debug;
+
+use crate::{
+    exec::{generation, IntoArgs},
+    sys::{
+        libphp_callable_call, libphp_callable_resolve, zend_fcall_info_cache, zval,
+        LIBPHP_FUNCTION_NOT_FOUND,
+    },
+    Error,
+};
+
+use super::Value;
+
+/// A PHP callable, such as a function name, `"Foo::bar"`, `[$object, 'method']` or a `Closure`.
+///
+/// The callable is resolved once when it is created, so calling it repeatedly doesn't look up
+/// the function again. Once the Context it was resolved in is closed, calling it returns
+/// `Error::ContextClosed`.
+pub struct Callable {
+    value: Value,
+    name: String,
+    fcc: zend_fcall_info_cache,
+    generation: u64,
+}
+
+impl Callable {
+    /// Resolve a callable value, failing with `Error::NotCallable` if it can't be called.
+    pub fn new(value: impl Into<Value>) -> Result<Self, Error> {
+        let value = value.into();
+        let mut fcc = zend_fcall_info_cache::default();
+        let mut name = zval::default();
+        let mut reason = zval::default();
+
+        let status = unsafe {
+            libphp_callable_resolve(
+                value.as_ptr() as *mut zval,
+                &mut fcc,
+                &mut name,
+                &mut reason,
+            )
+        };
+
+        let name = Value::new(&name).to_string();
+        let reason = Value::new(&reason);
+
+        if status == LIBPHP_FUNCTION_NOT_FOUND {
+            return Err(Error::NotCallable(if reason.is_null() {
+                name
+            } else {
+                reason.to_string()
+            }));
+        }
+
+        Error::from_status(status, &name)?;
+
+        Ok(Self {
+            value,
+            name,
+            fcc,
+            generation: generation(),
+        })
+    }
+
+    /// Get the name of the callable, e.g. `Foo::bar` or `Closure::__invoke`.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Call the callable with the given arguments.
+    pub fn call(&self, args: impl IntoArgs) -> Result<Value, Error> {
+        // The resolved function and object were freed when the engine shut down.
+        if self.generation != generation() {
+            return Err(Error::ContextClosed);
+        }
+
+        let args = args.into_args();
+        let mut retval = zval::default();
+
+        let status = args.with_params(|param_count, params, named_params| unsafe {
+            libphp_callable_call(
+                self.value.as_ptr() as *mut zval,
+                &self.fcc,
+                &mut retval,
+                param_count,
+                params,
+                named_params,
+            )
+        });
+
+        let value = Value::new(&retval);
+        Error::from_status(status, &self.name)?;
+
+        Ok(value)
+    }
+}
+
+impl From<Callable> for Value {
+    fn from(callable: Callable) -> Self {
+        callable.value
+    }
+}
+
+impl Debug for Callable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Callable({})", self.name)
+    }
+}
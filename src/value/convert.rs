@@ -0,0 +1,306 @@
+use std::fmt::Display;
+
+use crate::exec::Type;
+
+use super::{PhpString, Value};
+
+/// The reason a Value could not be converted to a Rust type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConversionError {
+    /// The value is not of the expected PHP type.
+    TypeMismatch {
+        expected: &'static str,
+        found: &'static str,
+    },
+    /// The value is of the right type, but does not fit in the target type.
+    OutOfRange { value: String, target: &'static str },
+    /// The string is not valid UTF-8.
+    InvalidUtf8,
+}
+
+impl ConversionError {
+    fn mismatch(expected: &'static str, value: &Value) -> Self {
+        Self::TypeMismatch {
+            expected,
+            found: value.get_type_name(),
+        }
+    }
+}
+
+impl Display for ConversionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TypeMismatch { expected, found } => {
+                write!(f, "expected a value of type {}, found {}", expected, found)
+            }
+            Self::OutOfRange { value, target } => {
+                write!(f, "{} is out of range for type {}", value, target)
+            }
+            Self::InvalidUtf8 => write!(f, "string is not valid UTF-8"),
+        }
+    }
+}
+
+impl std::error::Error for ConversionError {}
+
+impl From<()> for Value {
+    fn from(_: ()) -> Self {
+        Value::null()
+    }
+}
+
+/// None becomes null.
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(value: Option<T>) -> Self {
+        match value {
+            Some(value) => value.into(),
+            None => Value::null(),
+        }
+    }
+}
+
+impl TryFrom<&Value> for () {
+    type Error = ConversionError;
+
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        match value.is_null() {
+            true => Ok(()),
+            false => Err(ConversionError::mismatch("null", value)),
+        }
+    }
+}
+
+impl TryFrom<&Value> for bool {
+    type Error = ConversionError;
+
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        match value.is_bool() {
+            true => Ok(value.is_true()),
+            false => Err(ConversionError::mismatch("bool", value)),
+        }
+    }
+}
+
+impl TryFrom<&Value> for i64 {
+    type Error = ConversionError;
+
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        match value.is_int() {
+            true => Ok(value.to_int()),
+            false => Err(ConversionError::mismatch("int", value)),
+        }
+    }
+}
+
+macro_rules! impl_try_from_int {
+    ($($int:ty),*) => {
+        $(
+            impl TryFrom<&Value> for $int {
+                type Error = ConversionError;
+
+                fn try_from(value: &Value) -> Result<Self, Self::Error> {
+                    let int = i64::try_from(value)?;
+
+                    <$int>::try_from(int).map_err(|_| ConversionError::OutOfRange {
+                        value: int.to_string(),
+                        target: stringify!($int),
+                    })
+                }
+            }
+        )*
+    };
+}
+
+impl_try_from_int!(i8, i16, i32, isize, u8, u16, u32, u64, usize);
+
+/// Like PHP, an int is accepted where a float is expected.
+impl TryFrom<&Value> for f64 {
+    type Error = ConversionError;
+
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        if value.is_float() {
+            Ok(value.to_float())
+        } else if value.is_int() {
+            Ok(value.to_int() as f64)
+        } else {
+            Err(ConversionError::mismatch("float", value))
+        }
+    }
+}
+
+impl TryFrom<&Value> for f32 {
+    type Error = ConversionError;
+
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        f64::try_from(value).map(|float| float as f32)
+    }
+}
+
+impl TryFrom<&Value> for Vec<u8> {
+    type Error = ConversionError;
+
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        value
+            .as_bytes()
+            .map(<[u8]>::to_vec)
+            .ok_or_else(|| ConversionError::mismatch("string", value))
+    }
+}
+
+impl TryFrom<&Value> for String {
+    type Error = ConversionError;
+
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        String::from_utf8(Vec::try_from(value)?).map_err(|_| ConversionError::InvalidUtf8)
+    }
+}
+
+macro_rules! impl_try_from_value {
+    ($($ty:ty),*) => {
+        $(
+            impl TryFrom<Value> for $ty {
+                type Error = ConversionError;
+
+                fn try_from(value: Value) -> Result<Self, Self::Error> {
+                    <$ty>::try_from(&value)
+                }
+            }
+        )*
+    };
+}
+
+impl_try_from_value!(
+    (), bool, i8, i16, i32, i64, isize, u8, u16, u32, u64, usize, f32, f64, String, Vec<u8>
+);
+
+/// A Rust type that can be decoded from a PHP value, e.g. an argument passed to a Rust function.
+pub trait FromPhp: Sized {
+    /// The PHP type this decodes from, used in error messages and function signatures.
+    fn php_type() -> Type;
+
+    /// Decode the value, returning None if it is not of the expected type.
+    fn from_php(value: &Value) -> Option<Self>;
+}
+
+/// A Rust type that can be encoded as a PHP value, e.g. the return value of a Rust function.
+pub trait IntoPhp {
+    fn into_php(self) -> Value;
+}
+
+impl<T: Into<Value>> IntoPhp for T {
+    fn into_php(self) -> Value {
+        self.into()
+    }
+}
+
+impl FromPhp for Value {
+    fn php_type() -> Type {
+        Type::Mixed
+    }
+
+    fn from_php(value: &Value) -> Option<Self> {
+        Some(value.clone())
+    }
+}
+
+impl FromPhp for i64 {
+    fn php_type() -> Type {
+        Type::Int
+    }
+
+    fn from_php(value: &Value) -> Option<Self> {
+        value.try_into().ok()
+    }
+}
+
+impl FromPhp for f64 {
+    fn php_type() -> Type {
+        Type::Float
+    }
+
+    fn from_php(value: &Value) -> Option<Self> {
+        value.try_into().ok()
+    }
+}
+
+impl FromPhp for bool {
+    fn php_type() -> Type {
+        Type::Bool
+    }
+
+    fn from_php(value: &Value) -> Option<Self> {
+        value.try_into().ok()
+    }
+}
+
+impl FromPhp for String {
+    fn php_type() -> Type {
+        Type::String
+    }
+
+    fn from_php(value: &Value) -> Option<Self> {
+        value.try_into().ok()
+    }
+}
+
+impl FromPhp for Vec<u8> {
+    fn php_type() -> Type {
+        Type::String
+    }
+
+    fn from_php(value: &Value) -> Option<Self> {
+        value.try_into().ok()
+    }
+}
+
+impl FromPhp for PhpString {
+    fn php_type() -> Type {
+        Type::String
+    }
+
+    fn from_php(value: &Value) -> Option<Self> {
+        value.try_into().ok()
+    }
+}
+
+impl FromPhp for f32 {
+    fn php_type() -> Type {
+        Type::Float
+    }
+
+    fn from_php(value: &Value) -> Option<Self> {
+        value.try_into().ok()
+    }
+}
+
+macro_rules! impl_from_php_int {
+    ($($int:ty),*) => {
+        $(
+            impl FromPhp for $int {
+                fn php_type() -> Type {
+                    Type::Int
+                }
+
+                fn from_php(value: &Value) -> Option<Self> {
+                    value.try_into().ok()
+                }
+            }
+        )*
+    };
+}
+
+impl_from_php_int!(i8, i16, i32, isize, u8, u16, u32, u64, usize);
+
+impl<T: FromPhp> FromPhp for Option<T> {
+    fn php_type() -> Type {
+        T::php_type().nullable()
+    }
+
+    fn from_php(value: &Value) -> Option<Self> {
+        if value.is_null() {
+            Some(None)
+        } else {
+            T::from_php(value).map(Some)
+        }
+    }
+}
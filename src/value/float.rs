@@ -0,0 +1,21 @@
+use crate::sys::{libphp_zval_create_double, zval};
+
+use super::Value;
+
+impl From<f64> for Value {
+    fn from(value: f64) -> Self {
+        let mut zval = zval::default();
+
+        unsafe {
+            libphp_zval_create_double(&mut zval, value);
+        }
+
+        Self::new(&zval)
+    }
+}
+
+impl From<f32> for Value {
+    fn from(value: f32) -> Self {
+        f64::from(value).into()
+    }
+}
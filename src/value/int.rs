@@ -1,6 +1,6 @@
 use crate::sys::{zval, libphp_zval_create_long};
 
-use super::Value;
+use super::{ConversionError, Value};
 
 impl From<i64> for Value {
     fn from(value: i64) -> Self {
@@ -10,4 +10,43 @@ impl From<i64> for Value {
 
         Self::new(&zval)
     }
-}
\ No newline at end of file
+}
+
+macro_rules! impl_from_int {
+    ($($int:ty),*) => {
+        $(
+            impl From<$int> for Value {
+                fn from(value: $int) -> Self {
+                    i64::from(value).into()
+                }
+            }
+        )*
+    };
+}
+
+impl_from_int!(i8, i16, i32, u8, u16, u32);
+
+impl From<isize> for Value {
+    fn from(value: isize) -> Self {
+        (value as i64).into()
+    }
+}
+
+/// PHP ints are signed, so values above `i64::MAX` can't be represented.
+impl TryFrom<u64> for Value {
+    type Error = ConversionError;
+
+    fn try_from(value: u64) -> Result<Self, Self::Error> {
+        i64::try_from(value)
+            .map(Value::from)
+            .map_err(|_| ConversionError::OutOfRange { value: value.to_string(), target: "int" })
+    }
+}
+
+impl TryFrom<usize> for Value {
+    type Error = ConversionError;
+
+    fn try_from(value: usize) -> Result<Self, Self::Error> {
+        Value::try_from(value as u64)
+    }
+}
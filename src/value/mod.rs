@@ -4,29 +4,76 @@ use std::{
 };
 
 use crate::sys::{
-    libphp_var_export, libphp_zval_get_string, libphp_zval_get_type, zval, zval_ptr_dtor, IS_ARRAY,
-    IS_DOUBLE, IS_FALSE, IS_LONG, IS_NULL, IS_STRING, IS_TRUE,
+    libphp_var_export, libphp_zval_copy, libphp_zval_copy_deref, libphp_zval_create_null,
+    libphp_zval_create_reference, libphp_zval_display, libphp_zval_get_double,
+    libphp_zval_get_long, libphp_zval_get_type, libphp_zval_is_true, libphp_zval_ptr_dtor,
+    libphp_zval_string_bytes, zval, IS_ARRAY, IS_DOUBLE, IS_FALSE, IS_LONG, IS_NULL, IS_OBJECT,
+    IS_REFERENCE, IS_STRING, IS_TRUE,
 };
 
-use self::array::Array;
+use self::{array::Array, callable::Callable, object::Object};
 
 pub mod array;
+mod bool;
+mod borrowed;
+pub mod callable;
+mod convert;
+mod float;
+pub mod object;
+#[cfg(feature = "serde")]
+mod serde;
 mod string;
 mod int;
+#[cfg(test)]
+mod tests;
 
-#[derive(Clone)]
+pub use borrowed::ValueRef;
+pub use convert::{ConversionError, FromPhp, IntoPhp};
+pub use string::PhpString;
+#[cfg(feature = "serde")]
+pub use self::serde::{from_value, to_value, SerdeError};
+
+/// An owned PHP value.
+///
+/// Values follow PHP's reference counting: cloning a Value adds a reference to strings, arrays
+/// and objects rather than copying them, and dropping it releases that reference.
 pub struct Value {
     ptr: Box<zval>,
 }
 
 impl Value {
-    /// Create a new Value from an existing zval.
-    pub fn new(zval: &zval) -> Self {
+    /// Create a new Value from an existing zval, taking over its reference.
+    ///
+    /// The zval must not be released elsewhere, so this is only used for zvals the wrapper has just
+    /// filled in. Use `Value::from_zval` for zvals that are still owned by someone else.
+    pub(crate) fn new(zval: &zval) -> Self {
         Self {
             ptr: Box::new(*zval),
         }
     }
 
+    /// Create a new Value from a zval owned by someone else, adding a reference to it.
+    pub fn from_zval(zval: &zval) -> Self {
+        let mut copy = zval::default();
+
+        unsafe {
+            libphp_zval_copy(&mut copy, zval);
+        }
+
+        Self::new(&copy)
+    }
+
+    /// Create a new null Value.
+    pub fn null() -> Self {
+        let mut zval = zval::default();
+
+        unsafe {
+            libphp_zval_create_null(&mut zval);
+        }
+
+        Self::new(&zval)
+    }
+
     /// Get the type byte that represents the type of the value.
     pub fn get_type(&self) -> u8 {
         unsafe { libphp_zval_get_type(self.ptr.as_ref()) }
@@ -72,6 +119,11 @@ impl Value {
         self.get_type() == IS_ARRAY
     }
 
+    /// Check if the value is an object.
+    pub fn is_object(&self) -> bool {
+        self.get_type() == IS_OBJECT
+    }
+
     /// Check a raw pointer to the underlying zval.
     pub fn as_ptr(&self) -> *const zval {
         self.ptr.as_ref()
@@ -82,44 +134,81 @@ impl Value {
         self.ptr.as_mut()
     }
 
-    /// Convert the value to a string.
+    /// Get the value as UTF-8, or None if it is not a string or is not valid UTF-8.
     ///
-    /// WARNING: This method will panic if the PHP string is not valid UTF-8.
-    pub fn as_str(&self) -> &str {
-        unsafe {
-            let cstr = CStr::from_ptr(libphp_zval_get_string(self.ptr.as_ref()));
-            cstr.to_str().unwrap()
-        }
+    /// Unlike PHP, this never converts other types; use `to_php_string()` for that.
+    pub fn as_str(&self) -> Option<&str> {
+        std::str::from_utf8(self.as_bytes()?).ok()
     }
 
-    /// Convert the value to a slice of bytes.
+    /// Get the bytes of the value, or None if it is not a string.
     ///
-    /// WARNING: This method will panic if the PHP string is not valid UTF-8.
-    pub fn as_bytes(&self) -> &[u8] {
-        unsafe {
-            let cstr = CStr::from_ptr(libphp_zval_get_string(self.ptr.as_ref()));
-            cstr.to_bytes()
+    /// The bytes may contain NUL bytes or invalid UTF-8.
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        let mut len = 0;
+        let ptr = unsafe { libphp_zval_string_bytes(self.ptr.as_ref(), &mut len) };
+
+        if ptr.is_null() {
+            return None;
         }
+
+        Some(unsafe { std::slice::from_raw_parts(ptr as *const u8, len) })
     }
 
-    /// Convert the value to a C string (const char*).
-    pub fn as_cstr(&self) -> &CStr {
-        unsafe { CStr::from_ptr(libphp_zval_get_string(self.ptr.as_ref())) }
+    /// Get the value as a C string, or None if it is not a string or contains a NUL byte.
+    pub fn as_cstr(&self) -> Option<&CStr> {
+        let bytes = self.as_bytes()?;
+
+        // PHP strings are always followed by a NUL terminator, which isn't counted in their length.
+        let bytes = unsafe { std::slice::from_raw_parts(bytes.as_ptr(), bytes.len() + 1) };
+
+        CStr::from_bytes_with_nul(bytes).ok()
     }
 
-    /// Convert the value to a 64-bit integer.
+    /// Convert the value to a 64-bit integer, following PHP's `(int)` cast rules.
+    ///
+    /// Use `i64::try_from(&value)` to get an error instead when the value is not an int.
     pub fn to_int(&self) -> i64 {
-        unsafe { self.ptr.value.lval }
+        if self.is_int() {
+            return unsafe { self.ptr.value.lval };
+        }
+
+        unsafe { libphp_zval_get_long(self.ptr.as_ref()) }
     }
 
-    /// Convert the value to a 64-bit floating point number.
+    /// Convert the value to a 64-bit floating point number, following PHP's `(float)` cast rules.
+    ///
+    /// Use `f64::try_from(&value)` to get an error instead when the value is not a number.
     pub fn to_float(&self) -> f64 {
-        unsafe { self.ptr.value.dval }
+        if self.is_float() {
+            return unsafe { self.ptr.value.dval };
+        }
+
+        unsafe { libphp_zval_get_double(self.ptr.as_ref()) }
+    }
+
+    /// Convert the value to a boolean, following PHP's `(bool)` cast rules.
+    pub fn to_bool(&self) -> bool {
+        unsafe { libphp_zval_is_true(self.ptr.as_ref()) }
+    }
+
+    /// Convert the value to an Array, returning None if it is not an array.
+    pub fn to_array(&self) -> Option<Array> {
+        if !self.is_array() {
+            return None;
+        }
+
+        Some(unsafe { self.ptr.value.arr.into() })
+    }
+
+    /// Convert the value to a Callable, returning None if it can't be called.
+    pub fn to_callable(&self) -> Option<Callable> {
+        Callable::new(self.clone()).ok()
     }
 
-    /// Convert the value to an Array.
-    pub fn to_array(&self) -> Array {
-        unsafe { self.ptr.value.arr.into() }
+    /// Convert the value to an Object, returning None if it is not an object.
+    pub fn to_object(&self) -> Option<Object> {
+        Object::new(self)
     }
 
     /// Convert the value to null (unit type).
@@ -127,38 +216,111 @@ impl Value {
     /// NOTE: This method only exists for consistency, there's no reason to use it.
     pub fn to_null(&self) {}
 
+    /// Check if the value is a PHP reference, like one created with `&$value`.
+    pub fn is_reference(&self) -> bool {
+        self.get_type() == IS_REFERENCE
+    }
+
+    /// Create a PHP reference holding the given value.
+    ///
+    /// Passing a reference (or a clone of it) to a by-reference parameter lets the function
+    /// write to it, e.g. `preg_match`'s `$matches`. Use `dereference()` to read it back.
+    pub fn reference(value: impl Into<Value>) -> Self {
+        let mut value = value.into();
+        let mut zval = zval::default();
+
+        unsafe {
+            libphp_zval_create_reference(&mut zval, value.as_mut_ptr());
+        }
+
+        Self::new(&zval)
+    }
+
+    /// Get the value held by a reference, or a copy of the value itself if it is not one.
+    pub fn dereference(&self) -> Value {
+        let mut zval = zval::default();
+
+        unsafe {
+            libphp_zval_copy_deref(&mut zval, self.ptr.as_ref());
+        }
+
+        Self::new(&zval)
+    }
+
     /// Get a pretty name for the type of the value.
     pub fn get_type_name(&self) -> &'static str {
         match self.get_type() {
             IS_LONG => "int",
             IS_DOUBLE => "float",
             IS_NULL => "null",
+            IS_FALSE | IS_TRUE => "bool",
             IS_STRING => "string",
+            IS_ARRAY => "array",
+            IS_OBJECT => "object",
+            IS_REFERENCE => "reference",
             _ => "unknown",
         }
     }
 }
 
+/// Formats the value as PHP's `(string)` cast would, without changing it.
+///
+/// Objects that can't be converted to a string are formatted as an empty string.
+/// Formats the value as PHP's `(string)` cast would, without raising warnings or resetting the
+/// state execution results are read from. Objects that can't be converted are written as nothing.
 impl Display for Value {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.as_str())
+        if let Some(bytes) = self.as_bytes() {
+            return write!(f, "{}", String::from_utf8_lossy(bytes));
+        }
+
+        match self.get_type() {
+            IS_NULL | IS_FALSE => Ok(()),
+            IS_TRUE => write!(f, "1"),
+            IS_LONG => write!(f, "{}", unsafe { self.ptr.value.lval }),
+            _ => {
+                let mut string = zval::default();
+
+                unsafe { libphp_zval_display(self.ptr.as_ref(), &mut string) };
+
+                let string = Value::new(&string);
+
+                write!(
+                    f,
+                    "{}",
+                    String::from_utf8_lossy(string.as_bytes().unwrap_or_default())
+                )
+            }
+        }
     }
 }
 
 impl Debug for Value {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let var_exported = unsafe { libphp_var_export(self.ptr.as_ref()) };
+        let mut exported = zval::default();
+
+        unsafe { libphp_var_export(self.ptr.as_ref(), &mut exported) };
+
+        let exported = Value::new(&exported);
+
+        write!(
+            f,
+            "{}",
+            String::from_utf8_lossy(exported.as_bytes().unwrap_or_default())
+        )
+    }
+}
 
-        write!(f, "{}", unsafe {
-            CStr::from_ptr(var_exported).to_string_lossy()
-        })
+impl Clone for Value {
+    fn clone(&self) -> Self {
+        Self::from_zval(&self.ptr)
     }
 }
 
 impl Drop for Value {
     fn drop(&mut self) {
         unsafe {
-            zval_ptr_dtor(self.ptr.as_mut());
+            libphp_zval_ptr_dtor(self.ptr.as_mut());
         }
     }
 }
@@ -0,0 +1,134 @@
+use std::{ffi::CString, fmt::Debug};
+
+use crate::{
+    exec::IntoArgs,
+    sys::{
+        libphp_call_method, libphp_object_class_name, libphp_object_has_property,
+        libphp_object_instance_of, libphp_object_method_exists, libphp_object_read_property,
+        libphp_object_write_property, zval,
+    },
+    Error,
+};
+
+use super::Value;
+
+/// A PHP object, such as one returned from PHP code.
+///
+/// The object stays alive for as long as the Object (or any PHP variable) refers to it.
+pub struct Object {
+    value: Value,
+}
+
+impl Object {
+    /// Wrap a Value, returning None if it is not an object.
+    pub(crate) fn new(value: &Value) -> Option<Self> {
+        if !value.is_object() {
+            return None;
+        }
+
+        Some(Self {
+            value: value.clone(),
+        })
+    }
+
+    /// Get the name of the object's class, e.g. `DateTimeImmutable`.
+    pub fn class_name(&self) -> String {
+        let mut name = zval::default();
+
+        unsafe {
+            libphp_object_class_name(self.value.as_ptr() as *mut zval, &mut name);
+        }
+
+        Value::new(&name).to_string()
+    }
+
+    /// Check if the object is an instance of the given class or interface, like `instanceof`.
+    pub fn instance_of(&self, class: &str) -> bool {
+        let class_cstr = CString::new(class).unwrap();
+
+        unsafe { libphp_object_instance_of(self.value.as_ptr(), class_cstr.as_ptr()) }
+    }
+
+    /// Check if the object's class has a method with the given name, like `method_exists()`.
+    pub fn method_exists(&self, name: &str) -> bool {
+        let name_cstr = CString::new(name).unwrap();
+
+        unsafe { libphp_object_method_exists(self.value.as_ptr(), name_cstr.as_ptr()) }
+    }
+
+    /// Check if the object has a property with the given name, even if it is null.
+    pub fn has_property(&self, name: &str) -> bool {
+        let name_cstr = CString::new(name).unwrap();
+
+        unsafe { libphp_object_has_property(self.value.as_ptr(), name_cstr.as_ptr()) }
+    }
+
+    /// Read a property. Protected and private properties can be read too.
+    ///
+    /// Reading a property that does not exist returns null, unless the class defines `__get()`.
+    pub fn get_property(&self, name: &str) -> Result<Value, Error> {
+        let name_cstr = CString::new(name).unwrap();
+        let mut value = zval::default();
+
+        let status = unsafe {
+            libphp_object_read_property(self.value.as_ptr(), name_cstr.as_ptr(), &mut value)
+        };
+
+        let value = Value::new(&value);
+        Error::from_status(status, name)?;
+
+        Ok(value)
+    }
+
+    /// Write a property. Protected and private properties can be written too.
+    pub fn set_property(&mut self, name: &str, value: impl Into<Value>) -> Result<(), Error> {
+        let name_cstr = CString::new(name).unwrap();
+        let mut value = value.into();
+
+        let status = unsafe {
+            libphp_object_write_property(
+                self.value.as_ptr(),
+                name_cstr.as_ptr(),
+                value.as_mut_ptr(),
+            )
+        };
+
+        Error::from_status(status, name)
+    }
+
+    /// Call a public method on the object with the given arguments.
+    pub fn call_method(&mut self, name: &str, args: impl IntoArgs) -> Result<Value, Error> {
+        let name_cstr = CString::new(name).unwrap();
+        let args = args.into_args();
+        let object = self.value.as_mut_ptr();
+        let mut retval = zval::default();
+
+        let status = args.with_params(|param_count, params, named_params| unsafe {
+            libphp_call_method(
+                object,
+                name_cstr.as_ptr(),
+                &mut retval,
+                param_count,
+                params,
+                named_params,
+            )
+        });
+
+        let value = Value::new(&retval);
+        Error::from_status(status, &format!("{}::{}", self.class_name(), name))?;
+
+        Ok(value)
+    }
+}
+
+impl From<Object> for Value {
+    fn from(object: Object) -> Self {
+        object.value
+    }
+}
+
+impl Debug for Object {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self.value)
+    }
+}
@@ -0,0 +1,723 @@
+//! Conversion between Rust types and PHP values using serde.
+//!
+//! Structs and maps become associative arrays, sequences and tuples become lists, `None` and `()`
+//! become null, and enums follow serde's externally tagged representation: unit variants become
+//! their name and other variants become a single-element array keyed by their name.
+
+use std::fmt::Display;
+
+use ::serde::{
+    de::{
+        self, DeserializeOwned, DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess,
+        SeqAccess, VariantAccess, Visitor,
+    },
+    forward_to_deserialize_any, ser, Deserializer as _, Serialize,
+};
+
+use super::{
+    array::{Array, ArrayKey},
+    Value,
+};
+
+/// An error produced while converting between a Rust type and a PHP value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SerdeError {
+    message: String,
+}
+
+impl Display for SerdeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for SerdeError {}
+
+impl ser::Error for SerdeError {
+    fn custom<T: Display>(msg: T) -> Self {
+        Self {
+            message: msg.to_string(),
+        }
+    }
+}
+
+impl de::Error for SerdeError {
+    fn custom<T: Display>(msg: T) -> Self {
+        Self {
+            message: msg.to_string(),
+        }
+    }
+}
+
+/// Convert a Rust value into a PHP value.
+pub fn to_value<T: Serialize + ?Sized>(value: &T) -> Result<Value, SerdeError> {
+    value.serialize(Serializer)
+}
+
+/// Convert a PHP value into a Rust value.
+pub fn from_value<T: DeserializeOwned>(value: &Value) -> Result<T, SerdeError> {
+    T::deserialize(Deserializer {
+        value: value.clone(),
+    })
+}
+
+struct Serializer;
+
+impl ser::Serializer for Serializer {
+    type Ok = Value;
+    type Error = SerdeError;
+    type SerializeSeq = SerializeArray;
+    type SerializeTuple = SerializeArray;
+    type SerializeTupleStruct = SerializeArray;
+    type SerializeTupleVariant = SerializeVariant<SerializeArray>;
+    type SerializeMap = SerializeMap;
+    type SerializeStruct = SerializeMap;
+    type SerializeStructVariant = SerializeVariant<SerializeMap>;
+
+    fn serialize_bool(self, v: bool) -> Result<Value, SerdeError> {
+        Ok(v.into())
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Value, SerdeError> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Value, SerdeError> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Value, SerdeError> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Value, SerdeError> {
+        Ok(v.into())
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Value, SerdeError> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Value, SerdeError> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Value, SerdeError> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Value, SerdeError> {
+        i64::try_from(v)
+            .map(Value::from)
+            .map_err(|_| ser::Error::custom(format!("{} is too large for a PHP int", v)))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Value, SerdeError> {
+        self.serialize_f64(v.into())
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Value, SerdeError> {
+        Ok(v.into())
+    }
+
+    fn serialize_char(self, v: char) -> Result<Value, SerdeError> {
+        self.serialize_str(v.encode_utf8(&mut [0; 4]))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Value, SerdeError> {
+        Ok(Value::from_bytes(v.as_bytes()))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Value, SerdeError> {
+        Ok(Value::from_bytes(v))
+    }
+
+    fn serialize_none(self) -> Result<Value, SerdeError> {
+        Ok(Value::null())
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Value, SerdeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Value, SerdeError> {
+        Ok(Value::null())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Value, SerdeError> {
+        Ok(Value::null())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Value, SerdeError> {
+        self.serialize_str(variant)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Value, SerdeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Value, SerdeError> {
+        let mut array = Array::new();
+        array.insert(variant, to_value(value)?);
+
+        Ok(array.into())
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SerializeArray, SerdeError> {
+        Ok(SerializeArray::with_len(len))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SerializeArray, SerdeError> {
+        Ok(SerializeArray::with_len(Some(len)))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<SerializeArray, SerdeError> {
+        Ok(SerializeArray::with_len(Some(len)))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeVariant<SerializeArray>, SerdeError> {
+        Ok(SerializeVariant {
+            variant,
+            inner: SerializeArray::with_len(Some(len)),
+        })
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<SerializeMap, SerdeError> {
+        Ok(SerializeMap::with_len(len))
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<SerializeMap, SerdeError> {
+        Ok(SerializeMap::with_len(Some(len)))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeVariant<SerializeMap>, SerdeError> {
+        Ok(SerializeVariant {
+            variant,
+            inner: SerializeMap::with_len(Some(len)),
+        })
+    }
+}
+
+/// Builds a list from a sequence or tuple.
+struct SerializeArray {
+    array: Array,
+}
+
+impl SerializeArray {
+    fn with_len(len: Option<usize>) -> Self {
+        Self {
+            array: len
+                .and_then(|len| u32::try_from(len).ok())
+                .map(Array::with_capacity)
+                .unwrap_or_default(),
+        }
+    }
+
+    fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        if !self.array.push(to_value(value)?) {
+            return Err(ser::Error::custom("the array is full"));
+        }
+
+        Ok(())
+    }
+}
+
+impl ser::SerializeSeq for SerializeArray {
+    type Ok = Value;
+    type Error = SerdeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, SerdeError> {
+        Ok(self.array.into())
+    }
+}
+
+impl ser::SerializeTuple for SerializeArray {
+    type Ok = Value;
+    type Error = SerdeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, SerdeError> {
+        Ok(self.array.into())
+    }
+}
+
+impl ser::SerializeTupleStruct for SerializeArray {
+    type Ok = Value;
+    type Error = SerdeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, SerdeError> {
+        Ok(self.array.into())
+    }
+}
+
+/// Builds an associative array from a map or struct.
+struct SerializeMap {
+    array: Array,
+    key: Option<ArrayKey>,
+}
+
+impl SerializeMap {
+    fn with_len(len: Option<usize>) -> Self {
+        Self {
+            array: len
+                .and_then(|len| u32::try_from(len).ok())
+                .map(Array::with_capacity)
+                .unwrap_or_default(),
+            key: None,
+        }
+    }
+}
+
+impl ser::SerializeMap for SerializeMap {
+    type Ok = Value;
+    type Error = SerdeError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), SerdeError> {
+        let key = to_value(key)?;
+
+        self.key = Some(if key.is_int() {
+            ArrayKey::Int(key.to_int())
+        } else if let Some(key) = key.as_bytes() {
+            ArrayKey::String(key.to_vec())
+        } else {
+            return Err(ser::Error::custom(format!(
+                "array keys must be integers or strings, not {}",
+                key.get_type_name()
+            )));
+        });
+
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| ser::Error::custom("a map value was serialized before its key"))?;
+
+        self.array.insert(key, to_value(value)?);
+
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value, SerdeError> {
+        Ok(self.array.into())
+    }
+}
+
+impl ser::SerializeStruct for SerializeMap {
+    type Ok = Value;
+    type Error = SerdeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), SerdeError> {
+        self.array.insert(key, to_value(value)?);
+
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value, SerdeError> {
+        Ok(self.array.into())
+    }
+}
+
+/// Wraps the array built for a tuple or struct variant in `[variant => array]`.
+struct SerializeVariant<T> {
+    variant: &'static str,
+    inner: T,
+}
+
+impl<T> SerializeVariant<T> {
+    fn wrap(variant: &str, inner: Array) -> Result<Value, SerdeError> {
+        let mut array = Array::new();
+        array.insert(variant, inner);
+
+        Ok(array.into())
+    }
+}
+
+impl ser::SerializeTupleVariant for SerializeVariant<SerializeArray> {
+    type Ok = Value;
+    type Error = SerdeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.inner.push(value)
+    }
+
+    fn end(self) -> Result<Value, SerdeError> {
+        Self::wrap(self.variant, self.inner.array)
+    }
+}
+
+impl ser::SerializeStructVariant for SerializeVariant<SerializeMap> {
+    type Ok = Value;
+    type Error = SerdeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), SerdeError> {
+        ser::SerializeStruct::serialize_field(&mut self.inner, key, value)
+    }
+
+    fn end(self) -> Result<Value, SerdeError> {
+        Self::wrap(self.variant, self.inner.array)
+    }
+}
+
+struct Deserializer {
+    value: Value,
+}
+
+impl Deserializer {
+    fn invalid_type(&self, expected: &str) -> SerdeError {
+        de::Error::custom(format!(
+            "invalid type: {}, expected {}",
+            self.value.get_type_name(),
+            expected
+        ))
+    }
+}
+
+/// Decode an array key or variant name, which Rust requires to be valid UTF-8.
+fn utf8_name(bytes: Vec<u8>, what: &str) -> Result<String, SerdeError> {
+    String::from_utf8(bytes).map_err(|error| {
+        de::Error::custom(format!(
+            "{} {:?} is not valid UTF-8",
+            what,
+            String::from_utf8_lossy(error.as_bytes())
+        ))
+    })
+}
+
+/// Check if the array's keys are 0, 1, 2... in order, as they are for a list.
+fn is_list(array: &Array) -> bool {
+    array
+        .keys()
+        .enumerate()
+        .all(|(index, key)| key == ArrayKey::Int(index as i64))
+}
+
+impl<'de> de::Deserializer<'de> for Deserializer {
+    type Error = SerdeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        let value = &self.value;
+
+        if value.is_null() {
+            visitor.visit_unit()
+        } else if value.is_bool() {
+            visitor.visit_bool(value.is_true())
+        } else if value.is_int() {
+            visitor.visit_i64(value.to_int())
+        } else if value.is_float() {
+            visitor.visit_f64(value.to_float())
+        } else if let Some(bytes) = value.as_bytes() {
+            match String::from_utf8(bytes.to_vec()) {
+                Ok(string) => visitor.visit_string(string),
+                Err(error) => visitor.visit_byte_buf(error.into_bytes()),
+            }
+        } else if let Some(array) = value.to_array() {
+            if is_list(&array) {
+                visitor.visit_seq(ArrayAccess::new(&array))
+            } else {
+                visitor.visit_map(ArrayAccess::new(&array))
+            }
+        } else {
+            Err(self.invalid_type("a scalar, null or array"))
+        }
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        match self.value.to_array() {
+            Some(array) => visitor.visit_seq(ArrayAccess::new(&array)),
+            None => Err(self.invalid_type("an array")),
+        }
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        // An empty array is both a list and a map, so read any array as a map when one is expected.
+        match self.value.to_array() {
+            Some(array) => visitor.visit_map(ArrayAccess::new(&array)),
+            None => Err(self.invalid_type("an array")),
+        }
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        if self.value.is_null() {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        if let Some(variant) = self.value.as_bytes() {
+            let variant = utf8_name(variant.to_vec(), "variant name")?;
+
+            return visitor.visit_enum(variant.into_deserializer());
+        }
+
+        if let Some(array) = self.value.to_array() {
+            if let (1, Some((_, ArrayKey::String(variant), value))) =
+                (array.len(), array.iter().next())
+            {
+                let variant = utf8_name(variant, "variant name")?;
+
+                return visitor.visit_enum(Enum { variant, value });
+            }
+        }
+
+        Err(self.invalid_type("a variant name or an array with a single variant key"))
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct tuple tuple_struct identifier ignored_any
+    }
+}
+
+/// Walks the entries of an array, as either a sequence or a map.
+struct ArrayAccess {
+    entries: std::vec::IntoIter<(ArrayKey, Value)>,
+    value: Option<Value>,
+}
+
+impl ArrayAccess {
+    fn new(array: &Array) -> Self {
+        Self {
+            entries: array
+                .iter()
+                .map(|(_, key, value)| (key, value))
+                .collect::<Vec<_>>()
+                .into_iter(),
+            value: None,
+        }
+    }
+}
+
+impl<'de> SeqAccess<'de> for ArrayAccess {
+    type Error = SerdeError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, SerdeError> {
+        self.entries
+            .next()
+            .map(|(_, value)| seed.deserialize(Deserializer { value }))
+            .transpose()
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.entries.len())
+    }
+}
+
+impl<'de> MapAccess<'de> for ArrayAccess {
+    type Error = SerdeError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, SerdeError> {
+        let Some((key, value)) = self.entries.next() else {
+            return Ok(None);
+        };
+
+        self.value = Some(value);
+
+        seed.deserialize(KeyDeserializer { key }).map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, SerdeError> {
+        let value = self
+            .value
+            .take()
+            .ok_or_else(|| de::Error::custom("a map value was requested before its key"))?;
+
+        seed.deserialize(Deserializer { value })
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.entries.len())
+    }
+}
+
+/// Deserializes an array key. Integer keys can be read as strings too, since PHP turns numeric
+/// string keys into integers.
+struct KeyDeserializer {
+    key: ArrayKey,
+}
+
+impl KeyDeserializer {
+    fn into_string(self) -> Result<String, SerdeError> {
+        match self.key {
+            ArrayKey::Int(key) => Ok(key.to_string()),
+            ArrayKey::String(key) => utf8_name(key, "array key"),
+        }
+    }
+}
+
+impl<'de> de::Deserializer<'de> for KeyDeserializer {
+    type Error = SerdeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        match self.key {
+            ArrayKey::Int(key) => visitor.visit_i64(key),
+            ArrayKey::String(_) => visitor.visit_string(self.into_string()?),
+        }
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        visitor.visit_string(self.into_string()?)
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        visitor.visit_string(self.into_string()?)
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        visitor.visit_string(self.into_string()?)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char
+        bytes byte_buf option unit unit_struct seq tuple tuple_struct map struct
+        enum ignored_any
+    }
+}
+
+/// The variant and contents of an enum stored as `[variant => value]`.
+struct Enum {
+    variant: String,
+    value: Value,
+}
+
+impl<'de> EnumAccess<'de> for Enum {
+    type Error = SerdeError;
+    type Variant = Deserializer;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, Deserializer), SerdeError> {
+        let variant = seed.deserialize(self.variant.into_deserializer())?;
+
+        Ok((variant, Deserializer { value: self.value }))
+    }
+}
+
+impl<'de> VariantAccess<'de> for Deserializer {
+    type Error = SerdeError;
+
+    fn unit_variant(self) -> Result<(), SerdeError> {
+        if self.value.is_null() {
+            Ok(())
+        } else {
+            Err(self.invalid_type("null"))
+        }
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<T::Value, SerdeError> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        self.deserialize_any(visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        self.deserialize_any(visitor)
+    }
+}
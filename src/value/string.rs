@@ -1,19 +1,242 @@
-use std::ffi::CString;
+use std::{
+    borrow::Cow,
+    ffi::c_char,
+    fmt::{Debug, Display},
+    ptr::NonNull,
+    str::Utf8Error,
+};
 
-use crate::sys::{libphp_zval_create_string, zval};
+use crate::{
+    sys::{
+        libphp_string_addref, libphp_string_bytes, libphp_string_create, libphp_string_release,
+        libphp_zval_create_str, libphp_zval_create_stringl, libphp_zval_get_str,
+        libphp_zval_to_string, zend_string, zval,
+    },
+    Error,
+};
 
-use super::Value;
+use super::{ConversionError, Value};
 
-impl From<&str> for Value {
+/// A PHP string.
+///
+/// PHP strings are length-prefixed byte buffers, so they may contain NUL bytes or invalid UTF-8.
+pub struct PhpString {
+    ptr: NonNull<zend_string>,
+}
+
+impl PhpString {
+    /// Create a string by copying the given bytes.
+    pub fn new(bytes: impl AsRef<[u8]>) -> Self {
+        let bytes = bytes.as_ref();
+
+        unsafe {
+            let ptr = libphp_string_create(bytes.as_ptr() as *const c_char, bytes.len());
+
+            Self {
+                ptr: NonNull::new_unchecked(ptr),
+            }
+        }
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        let mut len = 0;
+
+        unsafe {
+            let ptr = libphp_string_bytes(self.ptr.as_ptr(), &mut len);
+
+            std::slice::from_raw_parts(ptr as *const u8, len)
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.as_bytes().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Get the string as UTF-8, failing if it contains invalid UTF-8.
+    pub fn to_str(&self) -> Result<&str, Utf8Error> {
+        std::str::from_utf8(self.as_bytes())
+    }
+
+    /// Get the string as UTF-8, replacing invalid sequences with U+FFFD.
+    pub fn to_string_lossy(&self) -> Cow<'_, str> {
+        String::from_utf8_lossy(self.as_bytes())
+    }
+
+    /// Wrap an existing, non-null string, adding a reference to it.
+    unsafe fn from_ptr(ptr: *mut zend_string) -> Self {
+        libphp_string_addref(ptr);
+
+        Self {
+            ptr: NonNull::new_unchecked(ptr),
+        }
+    }
+}
+
+impl Clone for PhpString {
+    fn clone(&self) -> Self {
+        unsafe { Self::from_ptr(self.ptr.as_ptr()) }
+    }
+}
+
+impl Drop for PhpString {
+    fn drop(&mut self) {
+        unsafe {
+            libphp_string_release(self.ptr.as_ptr());
+        }
+    }
+}
+
+impl Default for PhpString {
+    fn default() -> Self {
+        Self::new([])
+    }
+}
+
+impl PartialEq for PhpString {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_bytes() == other.as_bytes()
+    }
+}
+
+impl Eq for PhpString {}
+
+impl AsRef<[u8]> for PhpString {
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl Debug for PhpString {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self.to_string_lossy())
+    }
+}
+
+impl Display for PhpString {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_string_lossy())
+    }
+}
+
+impl From<&[u8]> for PhpString {
+    fn from(value: &[u8]) -> Self {
+        Self::new(value)
+    }
+}
+
+impl From<Vec<u8>> for PhpString {
+    fn from(value: Vec<u8>) -> Self {
+        Self::new(value)
+    }
+}
+
+impl From<&str> for PhpString {
     fn from(value: &str) -> Self {
+        Self::new(value)
+    }
+}
+
+impl From<String> for PhpString {
+    fn from(value: String) -> Self {
+        Self::new(value)
+    }
+}
+
+impl From<PhpString> for Value {
+    fn from(string: PhpString) -> Self {
+        let mut zval = zval::default();
+
+        unsafe {
+            libphp_zval_create_str(&mut zval, string.ptr.as_ptr());
+        }
+
+        // The Value takes over the PhpString's reference.
+        std::mem::forget(string);
+
+        Value::new(&zval)
+    }
+}
+
+impl TryFrom<&Value> for PhpString {
+    type Error = ConversionError;
+
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        let ptr = unsafe { libphp_zval_get_str(value.as_ptr()) };
+
+        if ptr.is_null() {
+            return Err(ConversionError::TypeMismatch {
+                expected: "string",
+                found: value.get_type_name(),
+            });
+        }
+
+        Ok(unsafe { PhpString::from_ptr(ptr) })
+    }
+}
+
+impl TryFrom<Value> for PhpString {
+    type Error = ConversionError;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        PhpString::try_from(&value)
+    }
+}
+
+impl Value {
+    /// Convert the value to a string, following PHP's `(string)` cast rules.
+    ///
+    /// The value itself is left unchanged. Converting an object without a `__toString()` method
+    /// fails with the thrown `Error`.
+    pub fn to_php_string(&self) -> Result<PhpString, Error> {
+        if let Ok(string) = PhpString::try_from(self) {
+            return Ok(string);
+        }
+
+        let mut result = zval::default();
+        let status = unsafe { libphp_zval_to_string(self.as_ptr(), &mut result) };
+        let result = Value::new(&result);
+
+        Error::from_status(status, "")?;
+
+        Ok(PhpString::try_from(&result).unwrap_or_default())
+    }
+
+    /// Create a string Value from raw bytes, which may contain NUL bytes or invalid UTF-8.
+    pub fn from_bytes(bytes: &[u8]) -> Self {
         let mut zval = zval::default();
-        let string = value.to_string();
-        let cstr = CString::new(string).unwrap();
 
         unsafe {
-            libphp_zval_create_string(&mut zval, cstr.as_ptr());
+            libphp_zval_create_stringl(&mut zval, bytes.as_ptr() as *const c_char, bytes.len());
         }
 
         Self::new(&zval)
     }
 }
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Self::from_bytes(value.as_bytes())
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        value.as_str().into()
+    }
+}
+
+impl From<&[u8]> for Value {
+    fn from(value: &[u8]) -> Self {
+        Self::from_bytes(value)
+    }
+}
+
+impl From<Vec<u8>> for Value {
+    fn from(value: Vec<u8>) -> Self {
+        Self::from_bytes(&value)
+    }
+}
@@ -0,0 +1,165 @@
+//! Reference counting tests.
+//!
+//! The PHP built by build.rs is a release build, whose allocator doesn't report leaks at shutdown
+//! the way a `--enable-debug` build does. Instead, `assert_no_leaks` checks that the Zend heap is
+//! back to the same size after an operation, and a double free corrupts the heap and aborts.
+
+use std::sync::Mutex;
+
+use crate::{
+    exec::Context,
+    sys::{libphp_memory_usage, libphp_zval_refcount},
+};
+
+use super::{array::Array, Value};
+
+/// PHP runs a single context per process, so the tests take turns.
+static PHP: Mutex<()> = Mutex::new(());
+
+fn with_context(test: impl FnOnce(&mut Context)) {
+    let _guard = PHP.lock().unwrap_or_else(|error| error.into_inner());
+    let mut context = Context::new();
+
+    test(&mut context);
+}
+
+fn refcount(value: &Value) -> u32 {
+    unsafe { libphp_zval_refcount(value.as_ptr()) }
+}
+
+/// Run the operation twice and check that the second run frees everything it allocates. The
+/// first run fills caches that PHP keeps until the end of the request, such as interned strings.
+fn assert_no_leaks(context: &mut Context, operation: impl Fn(&mut Context)) {
+    context.init().unwrap();
+    operation(context);
+
+    let before = unsafe { libphp_memory_usage() };
+    operation(context);
+    let after = unsafe { libphp_memory_usage() };
+
+    assert_eq!(
+        before,
+        after,
+        "{} bytes leaked",
+        after.saturating_sub(before)
+    );
+}
+
+#[test]
+fn array_clone_and_drop() {
+    with_context(|context| {
+        let value = context.result_of("range(1, 3)").unwrap();
+        assert_eq!(refcount(&value), 1);
+
+        let clone = value.clone();
+        assert_eq!(refcount(&value), 2);
+        drop(clone);
+        assert_eq!(refcount(&value), 1);
+
+        let array = value.to_array().unwrap();
+        assert_eq!(refcount(&value), 2);
+
+        let copy = array.clone();
+        assert_eq!(refcount(&value), 3);
+        drop(copy);
+        drop(array);
+        assert_eq!(refcount(&value), 1);
+    });
+}
+
+#[test]
+fn array_entries() {
+    with_context(|context| {
+        let value = context.result_of("[str_repeat('a', 3)]").unwrap();
+        let array = value.to_array().unwrap();
+
+        let borrowed = array.get_ref(0_i64).unwrap();
+        assert_eq!(refcount(&borrowed), 1);
+
+        let owned = borrowed.to_value();
+        assert_eq!(refcount(&owned), 2);
+
+        drop(array);
+        drop(value);
+        assert_eq!(refcount(&owned), 1);
+    });
+}
+
+#[test]
+fn string_clone_and_drop() {
+    with_context(|context| {
+        let value = context.result_of("str_repeat('a', 3)").unwrap();
+        assert_eq!(refcount(&value), 1);
+
+        let clones = (0..3).map(|_| value.clone()).collect::<Vec<_>>();
+        assert_eq!(refcount(&value), 4);
+        drop(clones);
+        assert_eq!(refcount(&value), 1);
+    });
+}
+
+#[test]
+fn object_clone_and_drop() {
+    with_context(|context| {
+        let value = context.result_of("new ArrayObject()").unwrap();
+        assert_eq!(refcount(&value), 1);
+
+        let clone = value.clone();
+        assert_eq!(refcount(&value), 2);
+        drop(clone);
+        assert_eq!(refcount(&value), 1);
+
+        let object = value.to_object().unwrap();
+        assert_eq!(refcount(&value), 2);
+        drop(object);
+        assert_eq!(refcount(&value), 1);
+    });
+}
+
+#[test]
+fn non_arrays_are_not_arrays() {
+    with_context(|context| {
+        let value = context.result_of("new ArrayObject([1, 2, 3])").unwrap();
+
+        assert!(value.to_array().is_none());
+        assert!(Value::from(1_i64).to_array().is_none());
+    });
+}
+
+#[test]
+fn values_do_not_leak() {
+    with_context(|context| {
+        assert_no_leaks(context, |context| {
+            let value = context
+                .result_of("['numbers' => range(1, 100), 'object' => new ArrayObject()]")
+                .unwrap();
+            let clones = (0..100).map(|_| value.clone()).collect::<Vec<_>>();
+            let array = value.to_array().unwrap();
+
+            for (_, key, value) in array.iter() {
+                let _ = (key.to_string(), value.to_string(), format!("{:?}", value));
+            }
+
+            drop(clones);
+            drop(array);
+        });
+    });
+}
+
+#[test]
+fn arrays_do_not_leak() {
+    with_context(|context| {
+        assert_no_leaks(context, |_| {
+            let mut original = Array::from_iter([1_i64, 2, 3]);
+            let copy = original.clone();
+
+            // Modifying a shared array separates it, which must release the shared copy.
+            original.push("four");
+            original.insert("key", Value::from(vec![0_u8; 1000]));
+            original.remove(0_i64);
+
+            drop(copy);
+            drop(original);
+        });
+    });
+}
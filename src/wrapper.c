@@ -1,30 +1,163 @@
 #include "wrapper.h"
 
+/* The number of Rust callbacks (functions, methods and output or error handlers) that PHP is
+ * running, and whether one of them has caught a bailout that must be raised again. */
+static int libphp_callback_depth = 0;
+static bool libphp_bailout_pending = false;
+
+/* Called from zend_catch. A bailout abandons the PHP code that is running, which the host can
+ * only recover from at the top level. Inside a Rust callback, the bailout is raised again once
+ * the callback has returned, so that the zend_try around the PHP code that called it sees it. */
+static void libphp_catch_bailout(void)
+{
+    if (libphp_callback_depth > 0) {
+        libphp_bailout_pending = true;
+    }
+}
+
+static void libphp_enter_callback(void)
+{
+    libphp_callback_depth++;
+}
+
+static void libphp_leave_callback(void)
+{
+    libphp_callback_depth--;
+
+    if (libphp_bailout_pending) {
+        libphp_bailout_pending = false;
+        zend_bailout();
+    }
+}
+
 uint8_t libphp_zval_get_type(const zval* pz) {
     return zval_get_type(pz);
 }
 
-const char *libphp_zval_get_string(zval *pz)
+zend_long libphp_zval_get_long(const zval *pz)
+{
+    zend_long result = 0;
+
+    zend_try {
+        result = zval_get_long(pz);
+    } zend_catch {
+        libphp_catch_bailout();
+    } zend_end_try();
+
+    return result;
+}
+
+double libphp_zval_get_double(const zval *pz)
 {
-    convert_to_string(pz);
+    double result = 0;
+
+    zend_try {
+        result = zval_get_double(pz);
+    } zend_catch {
+        libphp_catch_bailout();
+    } zend_end_try();
+
+    return result;
+}
+
+bool libphp_zval_is_true(const zval *pz)
+{
+    bool result = false;
+
+    zend_try {
+        result = zend_is_true(pz);
+    } zend_catch {
+        libphp_catch_bailout();
+    } zend_end_try();
+
+    return result;
+}
+
+const char *libphp_zval_string_bytes(const zval *pz, size_t *len)
+{
+    if (Z_TYPE_P(pz) != IS_STRING) {
+        *len = 0;
+        return NULL;
+    }
+
+    *len = Z_STRLEN_P(pz);
+
     return Z_STRVAL_P(pz);
 }
 
+void libphp_zval_copy(zval *dst, zval *src)
+{
+    ZVAL_COPY(dst, src);
+}
+
+void libphp_zval_ptr_dtor(zval *pz)
+{
+    /* Releasing an object may run its destructor, which must not be allowed to
+     * bail out through the Rust frames that are dropping the value. */
+    zend_try {
+        zval_ptr_dtor(pz);
+    } zend_catch {
+        libphp_catch_bailout();
+    } zend_end_try();
+}
+
 zend_string* libphp_zend_string_init(const char *str)
 {
     return zend_string_init(ZEND_STRL(str), 0);
 }
 
-const char* libphp_var_export(zval *pz) 
+zend_string *libphp_string_create(const char *str, size_t len)
+{
+    return zend_string_init(str, len, 0);
+}
+
+const char *libphp_string_bytes(const zend_string *s, size_t *len)
+{
+    *len = ZSTR_LEN(s);
+
+    return ZSTR_VAL(s);
+}
+
+void libphp_string_addref(zend_string *s)
+{
+    zend_string_addref(s);
+}
+
+void libphp_string_release(zend_string *s)
+{
+    zend_string_release(s);
+}
+
+void libphp_zval_create_str(zval *pz, zend_string *s)
+{
+    ZVAL_STR(pz, s);
+}
+
+zend_string *libphp_zval_get_str(const zval *pz)
+{
+    if (Z_TYPE_P(pz) != IS_STRING) {
+        return NULL;
+    }
+
+    return Z_STR_P(pz);
+}
+
+void libphp_var_export(zval *pz, zval *result)
 {
     smart_str buf = {0};
     php_var_export_ex(pz, 1, &buf);
-    smart_str_0(&buf);
 
-    const char* exported = buf.s->val;
-    smart_str_free(&buf); 
+    ZVAL_STR(result, smart_str_extract(&buf));
+}
+
+uint32_t libphp_zval_refcount(const zval *pz)
+{
+    return Z_REFCOUNTED_P(pz) ? Z_REFCOUNT_P(pz) : 0;
+}
 
-    return exported;
+size_t libphp_memory_usage(void)
+{
+    return zend_memory_usage(false);
 }
 
 void libphp_zval_create_string(zval *pz, const char *str)
@@ -37,11 +170,252 @@ void libphp_zval_create_long(zval *pz, long l)
     ZVAL_LONG(pz, l);
 }
 
+void libphp_zval_create_null(zval *pz)
+{
+    ZVAL_NULL(pz);
+}
+
+void libphp_zval_create_bool(zval *pz, bool b)
+{
+    ZVAL_BOOL(pz, b);
+}
+
+void libphp_zval_create_double(zval *pz, double d)
+{
+    ZVAL_DOUBLE(pz, d);
+}
+
+void libphp_zval_create_stringl(zval *pz, const char *str, size_t len)
+{
+    ZVAL_STRINGL(pz, str, len);
+}
+
+void libphp_zval_copy_deref(zval *dst, const zval *src)
+{
+    ZVAL_COPY_DEREF(dst, src);
+}
+
+void libphp_zval_create_reference(zval *pz, zval *value)
+{
+    Z_TRY_ADDREF_P(value);
+    ZVAL_NEW_REF(pz, value);
+}
+
+void libphp_zval_create_array(zval *pz, HashTable *ht)
+{
+    ZVAL_ARR(pz, ht);
+
+    /* Immutable arrays (such as literals cached by opcache) are not refcounted. */
+    if (GC_FLAGS(ht) & IS_ARRAY_IMMUTABLE) {
+        Z_TYPE_FLAGS_P(pz) = 0;
+    }
+}
+
+void libphp_array_addref(HashTable *ht)
+{
+    GC_TRY_ADDREF(ht);
+}
+
+void libphp_array_release(HashTable *ht)
+{
+    /* Releasing the last reference may destroy objects, whose destructors must
+     * not bail out through Rust frames. */
+    zend_try {
+        zend_array_release(ht);
+    } zend_catch {
+        libphp_catch_bailout();
+    } zend_end_try();
+}
+
+/* Make sure the array is not shared before it is modified, copying it if it is. */
+void libphp_array_separate(HashTable **ht)
+{
+    if (GC_REFCOUNT(*ht) > 1) {
+        HashTable *copy = zend_array_dup(*ht);
+
+        GC_TRY_DELREF(*ht);
+        *ht = copy;
+    }
+}
+
+/* String keys follow PHP's rules, so "1" is the same key as 1. */
+static zval *libphp_array_find(HashTable *ht, const zval *key)
+{
+    if (Z_TYPE_P(key) == IS_LONG) {
+        return zend_hash_index_find(ht, Z_LVAL_P(key));
+    }
+
+    return zend_symtable_find(ht, Z_STR_P(key));
+}
+
+/* Find the value stored under a key, looking through symbol table slots and references. */
+zval *libphp_array_lookup(HashTable *ht, const zval *key)
+{
+    zval *found = libphp_array_find(ht, key);
+
+    if (!found) {
+        return NULL;
+    }
+
+    if (Z_TYPE_P(found) == IS_INDIRECT) {
+        found = Z_INDIRECT_P(found);
+
+        if (Z_TYPE_P(found) == IS_UNDEF) {
+            return NULL;
+        }
+    }
+
+    ZVAL_DEREF(found);
+
+    return found;
+}
+
+bool libphp_array_get(HashTable *ht, const zval *key, zval *value)
+{
+    zval *found = libphp_array_lookup(ht, key);
+
+    if (!found) {
+        ZVAL_NULL(value);
+        return false;
+    }
+
+    ZVAL_COPY(value, found);
+
+    return true;
+}
+
+void libphp_array_update(HashTable *ht, const zval *key, zval *value)
+{
+    Z_TRY_ADDREF_P(value);
+
+    if (Z_TYPE_P(key) == IS_LONG) {
+        zend_hash_index_update(ht, Z_LVAL_P(key), value);
+    } else {
+        zend_symtable_update(ht, Z_STR_P(key), value);
+    }
+}
+
+bool libphp_array_push(HashTable *ht, zval *value)
+{
+    if (!zend_hash_next_index_insert(ht, value)) {
+        return false;
+    }
+
+    Z_TRY_ADDREF_P(value);
+
+    return true;
+}
+
+bool libphp_array_remove(HashTable *ht, const zval *key, zval *removed)
+{
+    zval *found = libphp_array_find(ht, key);
+
+    if (!found) {
+        ZVAL_NULL(removed);
+        return false;
+    }
+
+    ZVAL_COPY_DEREF(removed, found);
+
+    if (Z_TYPE_P(key) == IS_LONG) {
+        zend_hash_index_del(ht, Z_LVAL_P(key));
+    } else {
+        zend_symtable_del(ht, Z_STR_P(key));
+    }
+
+    return true;
+}
+
 void libphp_register_variable(const char *key, zval *value)
 {
     zend_hash_str_update(&EG(symbol_table), key, strlen(key), value);
 }
 
+bool libphp_get_global(const char *name, zval *value)
+{
+    zval *found = zend_hash_str_find_ind(&EG(symbol_table), name, strlen(name));
+
+    if (!found) {
+        ZVAL_NULL(value);
+        return false;
+    }
+
+    ZVAL_COPY_DEREF(value, found);
+
+    return true;
+}
+
+void libphp_set_global(const char *name, zval *value)
+{
+    zval *var = zend_hash_str_find(&EG(symbol_table), name, strlen(name));
+    zval garbage;
+
+    if (!var) {
+        Z_TRY_ADDREF_P(value);
+        zend_hash_str_add_new(&EG(symbol_table), name, strlen(name), value);
+        return;
+    }
+
+    /* The main script's variables live in slots that the symbol table points to, and writing
+     * through references keeps variables bound with `global $name` in sync. */
+    if (Z_TYPE_P(var) == IS_INDIRECT) {
+        var = Z_INDIRECT_P(var);
+    }
+
+    ZVAL_DEREF(var);
+    ZVAL_COPY_VALUE(&garbage, var);
+    ZVAL_COPY(var, value);
+
+    zend_try {
+        zval_ptr_dtor(&garbage);
+    } zend_catch {
+        libphp_catch_bailout();
+    } zend_end_try();
+}
+
+bool libphp_unset_global(const char *name)
+{
+    bool existed = false;
+
+    zend_try {
+        existed = zend_hash_str_del_ind(&EG(symbol_table), name, strlen(name)) == SUCCESS;
+    } zend_catch {
+        libphp_catch_bailout();
+    } zend_end_try();
+
+    return existed;
+}
+
+/* Find the next defined global from the given position, skipping unset variables. */
+bool libphp_symbol_table_next(HashPosition *pos, zval *name, zval **value)
+{
+    zval *found;
+
+    while ((found = zend_hash_get_current_data_ex(&EG(symbol_table), pos)) != NULL) {
+        zend_string *key;
+        zend_ulong index;
+        int key_type = zend_hash_get_current_key_ex(&EG(symbol_table), &key, &index, pos);
+
+        zend_hash_move_forward_ex(&EG(symbol_table), pos);
+
+        if (Z_TYPE_P(found) == IS_INDIRECT) {
+            found = Z_INDIRECT_P(found);
+        }
+
+        if (key_type != HASH_KEY_IS_STRING || Z_TYPE_P(found) == IS_UNDEF) {
+            continue;
+        }
+
+        ZVAL_STR_COPY(name, key);
+        *value = found;
+        ZVAL_DEREF(*value);
+
+        return true;
+    }
+
+    return false;
+}
+
 void libphp_register_constant(const char *name, zval *value)
 {
     zend_constant c;
@@ -52,4 +426,1380 @@ void libphp_register_constant(const char *name, zval *value)
     c.name = zend_string_init_interned(name, strlen(name), 1);
 
     zend_register_constant(&c);
-}
\ No newline at end of file
+}
+
+static int libphp_exception_status(void)
+{
+    if (EG(exception) == NULL) {
+        return LIBPHP_SUCCESS;
+    }
+
+    /* exit() unwinds the stack by throwing an internal exception. */
+    if (zend_is_unwind_exit(EG(exception))) {
+        zend_clear_exception();
+        return LIBPHP_EXIT;
+    }
+
+    if (instanceof_function(EG(exception)->ce, zend_ce_parse_error)) {
+        return LIBPHP_PARSE_ERROR;
+    }
+
+    return LIBPHP_EXCEPTION;
+}
+
+static int libphp_bailout_status(void)
+{
+    libphp_catch_bailout();
+
+    /* An exception may still be pending if the bailout happened while it was
+     * being thrown, and it would otherwise leak into the next execution. */
+    zend_clear_exception();
+
+    return LIBPHP_BAILOUT;
+}
+
+/* Functions called from the host run without a calling frame, and Zend reports an exception that
+ * escapes to the top level as an uncaught fatal error. An empty frame stands in for the caller,
+ * so the exception is left in EG(exception) to be returned instead. */
+static void libphp_push_frame(zend_execute_data *frame)
+{
+    if (EG(current_execute_data) == NULL) {
+        memset(frame, 0, sizeof(zend_execute_data));
+        EG(current_execute_data) = frame;
+    }
+}
+
+static void libphp_pop_frame(zend_execute_data *frame)
+{
+    if (EG(current_execute_data) == frame) {
+        EG(current_execute_data) = NULL;
+    }
+}
+
+/* Prepare to run PHP code. Returns false if the PHP code that called the current Rust callback
+ * has bailed out, in which case nothing more may run until the callback returns. */
+static bool libphp_begin(void)
+{
+    if (libphp_bailout_pending) {
+        return false;
+    }
+
+    EG(exit_status) = 0;
+
+    if (PG(last_error_message)) {
+        zend_string_release(PG(last_error_message));
+        PG(last_error_message) = NULL;
+    }
+
+    return true;
+}
+
+int libphp_exit_status(void)
+{
+    return EG(exit_status);
+}
+
+bool libphp_last_error_message(zval *message)
+{
+    if (PG(last_error_message) == NULL) {
+        ZVAL_NULL(message);
+        return false;
+    }
+
+    ZVAL_STR_COPY(message, PG(last_error_message));
+
+    return true;
+}
+
+uint32_t libphp_last_error_lineno(void)
+{
+    return PG(last_error_lineno);
+}
+
+/* Run a compiled script and free it, as zend_execute_scripts() does. Must be called inside
+ * zend_try. */
+static void libphp_execute_main(zend_op_array *op_array, zval *retval)
+{
+    zend_execute(op_array, retval);
+    zend_destroy_static_vars(op_array);
+    destroy_op_array(op_array);
+    efree_size(op_array, sizeof(zend_op_array));
+}
+
+/* Run the auto_prepend_file or auto_append_file, if one is configured, as php_execute_script()
+ * does around the main script. Must be called inside zend_try. */
+static int libphp_execute_auto_file(const char *filename)
+{
+    zend_file_handle file_handle;
+    zend_op_array *op_array;
+    int status;
+
+    if (filename == NULL || filename[0] == '\0') {
+        return LIBPHP_SUCCESS;
+    }
+
+    zend_stream_init_filename(&file_handle, filename);
+    op_array = zend_compile_file(&file_handle, ZEND_REQUIRE);
+
+    if (op_array) {
+        libphp_execute_main(op_array, NULL);
+    }
+
+    status = op_array || EG(exception) ? libphp_exception_status() : LIBPHP_PARSE_ERROR;
+    zend_destroy_file_handle(&file_handle);
+
+    return status;
+}
+
+int libphp_execute_file(const char *filename, zval *retval)
+{
+    zend_file_handle file_handle;
+    zend_op_array *op_array;
+    int status = LIBPHP_SUCCESS;
+
+    ZVAL_NULL(retval);
+
+    if (!libphp_begin()) {
+        return LIBPHP_BAILOUT;
+    }
+
+    zend_stream_init_filename(&file_handle, filename);
+
+    if (zend_stream_open(&file_handle) == FAILURE) {
+        zend_destroy_file_handle(&file_handle);
+        return LIBPHP_FILE_NOT_FOUND;
+    }
+
+    if (file_handle.opened_path) {
+        zend_hash_add_empty_element(&EG(included_files), file_handle.opened_path);
+    }
+
+    zend_try {
+        status = libphp_execute_auto_file(PG(auto_prepend_file));
+
+        if (status == LIBPHP_SUCCESS) {
+            op_array = zend_compile_file(&file_handle, ZEND_REQUIRE);
+
+            if (op_array) {
+                libphp_execute_main(op_array, retval);
+            }
+
+            status = op_array || EG(exception) ? libphp_exception_status() : LIBPHP_PARSE_ERROR;
+        }
+
+        if (status == LIBPHP_SUCCESS) {
+            status = libphp_execute_auto_file(PG(auto_append_file));
+        }
+    } zend_catch {
+        status = libphp_bailout_status();
+    } zend_end_try();
+
+    zend_destroy_file_handle(&file_handle);
+
+    return status;
+}
+
+int libphp_eval_string(const char *code, const char *name, zval *retval)
+{
+    int status = LIBPHP_SUCCESS;
+
+    ZVAL_NULL(retval);
+
+    if (!libphp_begin()) {
+        return LIBPHP_BAILOUT;
+    }
+
+    zend_try {
+        zend_eval_string(code, retval, name);
+        status = libphp_exception_status();
+    } zend_catch {
+        status = libphp_bailout_status();
+    } zend_end_try();
+
+    return status;
+}
+
+/* Compile and run PHP code from memory, as though it were a file with the given name.
+ *
+ * The code either starts with PHP statements (after_open_tag) or is a whole file, which may
+ * begin with a shebang line and inline HTML before the first <?php tag. */
+int libphp_execute_string(const char *code, size_t len, const char *filename, bool after_open_tag, zval *retval)
+{
+    zend_string *source;
+    zend_op_array *op_array;
+    int status = LIBPHP_SUCCESS;
+
+    ZVAL_NULL(retval);
+
+    if (!libphp_begin()) {
+        return LIBPHP_BAILOUT;
+    }
+
+    source = zend_string_init(code, len, 0);
+
+    /* Only whole files get the auto_prepend_file and auto_append_file, not eval-style code. */
+    zend_try {
+        status = after_open_tag ? LIBPHP_SUCCESS : libphp_execute_auto_file(PG(auto_prepend_file));
+
+        if (status == LIBPHP_SUCCESS) {
+            op_array = zend_compile_string(source, filename, after_open_tag ? ZEND_COMPILE_POSITION_AFTER_OPEN_TAG : ZEND_COMPILE_POSITION_AT_SHEBANG);
+
+            if (op_array) {
+                libphp_execute_main(op_array, retval);
+            }
+
+            status = op_array || EG(exception) ? libphp_exception_status() : LIBPHP_PARSE_ERROR;
+        }
+
+        if (status == LIBPHP_SUCCESS && !after_open_tag) {
+            status = libphp_execute_auto_file(PG(auto_append_file));
+        }
+    } zend_catch {
+        status = libphp_bailout_status();
+    } zend_end_try();
+
+    zend_string_release(source);
+
+    return status;
+}
+
+int libphp_compile_file(const char *filename, zend_op_array **op_array)
+{
+    zend_file_handle file_handle;
+    int status = LIBPHP_SUCCESS;
+
+    *op_array = NULL;
+
+    if (!libphp_begin()) {
+        return LIBPHP_BAILOUT;
+    }
+
+    zend_stream_init_filename(&file_handle, filename);
+
+    if (zend_stream_open(&file_handle) == FAILURE) {
+        zend_destroy_file_handle(&file_handle);
+        return LIBPHP_FILE_NOT_FOUND;
+    }
+
+    zend_try {
+        *op_array = zend_compile_file(&file_handle, ZEND_REQUIRE);
+        status = *op_array || EG(exception) ? libphp_exception_status() : LIBPHP_PARSE_ERROR;
+    } zend_catch {
+        status = libphp_bailout_status();
+    } zend_end_try();
+
+    zend_destroy_file_handle(&file_handle);
+
+    return status;
+}
+
+int libphp_compile_string(const char *code, size_t len, const char *filename, bool after_open_tag, zend_op_array **op_array)
+{
+    zend_string *source;
+    int status = LIBPHP_SUCCESS;
+
+    *op_array = NULL;
+
+    if (!libphp_begin()) {
+        return LIBPHP_BAILOUT;
+    }
+
+    source = zend_string_init(code, len, 0);
+
+    zend_try {
+        *op_array = zend_compile_string(source, filename, after_open_tag ? ZEND_COMPILE_POSITION_AFTER_OPEN_TAG : ZEND_COMPILE_POSITION_AT_SHEBANG);
+        status = *op_array || EG(exception) ? libphp_exception_status() : LIBPHP_PARSE_ERROR;
+    } zend_catch {
+        status = libphp_bailout_status();
+    } zend_end_try();
+
+    zend_string_release(source);
+
+    return status;
+}
+
+int libphp_execute_op_array(zend_op_array *op_array, zval *retval)
+{
+    int status = LIBPHP_SUCCESS;
+
+    ZVAL_NULL(retval);
+
+    if (!libphp_begin()) {
+        return LIBPHP_BAILOUT;
+    }
+
+    zend_try {
+        zend_execute(op_array, retval);
+        status = libphp_exception_status();
+    } zend_catch {
+        status = libphp_bailout_status();
+    } zend_end_try();
+
+    return status;
+}
+
+void libphp_destroy_op_array(zend_op_array *op_array)
+{
+    zend_try {
+        zend_destroy_static_vars(op_array);
+        destroy_op_array(op_array);
+    } zend_catch {
+        libphp_catch_bailout();
+    } zend_end_try();
+
+    efree_size(op_array, sizeof(zend_op_array));
+}
+
+/* Remove the functions or classes added to a table since it had the given number of entries. */
+static void libphp_discard_declarations(HashTable *table, uint32_t count)
+{
+    while (zend_hash_num_elements(table) > count) {
+        HashPosition pos;
+        zend_string *key = NULL;
+        zend_ulong index;
+
+        zend_hash_internal_pointer_end_ex(table, &pos);
+        zend_hash_get_current_key_ex(table, &key, &index, &pos);
+
+        if (key) {
+            zend_hash_del(table, key);
+        } else {
+            zend_hash_index_del(table, index);
+        }
+    }
+}
+
+/* Compile code without executing it, like `php -l`.
+ *
+ * Top-level functions and classes are declared as they are compiled, so they're removed again to
+ * leave the context as it was. */
+static int libphp_lint(const char *filename, const char *code, size_t len)
+{
+    uint32_t function_count = zend_hash_num_elements(CG(function_table));
+    uint32_t class_count = zend_hash_num_elements(CG(class_table));
+    zend_op_array *op_array;
+    int status;
+
+    if (code) {
+        status = libphp_compile_string(code, len, filename, false, &op_array);
+    } else {
+        status = libphp_compile_file(filename, &op_array);
+    }
+
+    if (op_array) {
+        libphp_destroy_op_array(op_array);
+    }
+
+    zend_try {
+        libphp_discard_declarations(CG(function_table), function_count);
+        libphp_discard_declarations(CG(class_table), class_count);
+    } zend_catch {
+        libphp_catch_bailout();
+    } zend_end_try();
+
+    return status;
+}
+
+int libphp_lint_file(const char *filename)
+{
+    return libphp_lint(filename, NULL, 0);
+}
+
+int libphp_lint_string(const char *code, size_t len, const char *filename)
+{
+    return libphp_lint(filename, code, len);
+}
+
+int libphp_call_function(const char *name, zval *retval, uint32_t param_count, zval *params, HashTable *named_params)
+{
+    zend_execute_data frame;
+    zval function_name;
+    int status = LIBPHP_SUCCESS;
+
+    ZVAL_NULL(retval);
+
+    if (!libphp_begin()) {
+        return LIBPHP_BAILOUT;
+    }
+
+    ZVAL_STRING(&function_name, name);
+
+    /* Resolving a static method may autoload its class, which runs PHP code. */
+    libphp_push_frame(&frame);
+
+    zend_try {
+        if (zend_is_callable(&function_name, 0, NULL)) {
+            call_user_function_named(NULL, NULL, &function_name, retval, param_count, params, named_params);
+            status = libphp_exception_status();
+        } else {
+            status = EG(exception) ? libphp_exception_status() : LIBPHP_FUNCTION_NOT_FOUND;
+        }
+    } zend_catch {
+        status = libphp_bailout_status();
+    } zend_end_try();
+
+    libphp_pop_frame(&frame);
+    zval_ptr_dtor(&function_name);
+
+    return status;
+}
+
+bool libphp_exception_take(zval *exception)
+{
+    if (EG(exception) == NULL) {
+        ZVAL_NULL(exception);
+        return false;
+    }
+
+    ZVAL_OBJ_COPY(exception, EG(exception));
+    zend_clear_exception();
+
+    return true;
+}
+
+void libphp_exception_property(zval *exception, const char *name, zval *value)
+{
+    zend_object *object = Z_OBJ_P(exception);
+    zval rv, *property;
+
+    property = zend_read_property(zend_get_exception_base(object), object, name, strlen(name), 1, &rv);
+    ZVAL_COPY_DEREF(value, property);
+}
+
+void libphp_exception_trace_string(zval *exception, zval *trace)
+{
+    zend_call_method_with_0_params(Z_OBJ_P(exception), Z_OBJCE_P(exception), NULL, "gettraceasstring", trace);
+}
+
+void libphp_object_class_name(zval *object, zval *name)
+{
+    ZVAL_STR_COPY(name, Z_OBJCE_P(object)->name);
+}
+
+bool libphp_object_instance_of(const zval *object, const char *class_name)
+{
+    zend_string *name = zend_string_init(class_name, strlen(class_name), 0);
+    /* An object cannot be an instance of a class that has not been loaded. */
+    zend_class_entry *ce = zend_lookup_class_ex(name, NULL, ZEND_FETCH_CLASS_NO_AUTOLOAD);
+
+    zend_string_release(name);
+
+    return ce && instanceof_function(Z_OBJCE_P(object), ce);
+}
+
+bool libphp_object_method_exists(const zval *object, const char *name)
+{
+    return zend_hash_str_find_ptr_lc(&Z_OBJCE_P(object)->function_table, name, strlen(name)) != NULL;
+}
+
+/* Properties are accessed from the scope of the object's own class, so the host
+ * can see protected and private properties too. */
+bool libphp_object_has_property(const zval *object, const char *name)
+{
+    zend_class_entry *old_scope = EG(fake_scope);
+    zend_string *property = zend_string_init(name, strlen(name), 0);
+    bool exists = false;
+
+    EG(fake_scope) = Z_OBJCE_P(object);
+
+    zend_try {
+        exists = Z_OBJ_HT_P(object)->has_property(Z_OBJ_P(object), property, ZEND_PROPERTY_EXISTS, NULL);
+    } zend_catch {
+        libphp_catch_bailout();
+    } zend_end_try();
+
+    EG(fake_scope) = old_scope;
+    zend_string_release(property);
+
+    /* A throwing __isset() means the property cannot be said to exist. */
+    if (EG(exception)) {
+        zend_clear_exception();
+        exists = false;
+    }
+
+    return exists;
+}
+
+int libphp_object_read_property(const zval *object, const char *name, zval *value)
+{
+    zend_execute_data frame;
+    int status = LIBPHP_SUCCESS;
+    zval rv, *property;
+
+    ZVAL_NULL(value);
+
+    if (!libphp_begin()) {
+        return LIBPHP_BAILOUT;
+    }
+
+    libphp_push_frame(&frame);
+
+    zend_try {
+        property = zend_read_property(Z_OBJCE_P(object), Z_OBJ_P(object), name, strlen(name), 1, &rv);
+        ZVAL_COPY_DEREF(value, property);
+
+        if (property == &rv) {
+            zval_ptr_dtor(&rv);
+        }
+
+        status = libphp_exception_status();
+    } zend_catch {
+        status = libphp_bailout_status();
+    } zend_end_try();
+
+    libphp_pop_frame(&frame);
+
+    return status;
+}
+
+int libphp_object_write_property(const zval *object, const char *name, zval *value)
+{
+    zend_execute_data frame;
+    int status = LIBPHP_SUCCESS;
+
+    if (!libphp_begin()) {
+        return LIBPHP_BAILOUT;
+    }
+
+    libphp_push_frame(&frame);
+
+    zend_try {
+        zend_update_property(Z_OBJCE_P(object), Z_OBJ_P(object), name, strlen(name), value);
+        status = libphp_exception_status();
+    } zend_catch {
+        status = libphp_bailout_status();
+    } zend_end_try();
+
+    libphp_pop_frame(&frame);
+
+    return status;
+}
+
+int libphp_call_method(zval *object, const char *name, zval *retval, uint32_t param_count, zval *params, HashTable *named_params)
+{
+    zend_execute_data frame;
+    zval method_name;
+    int status = LIBPHP_SUCCESS;
+
+    ZVAL_NULL(retval);
+
+    if (!libphp_begin()) {
+        return LIBPHP_BAILOUT;
+    }
+
+    ZVAL_STRING(&method_name, name);
+
+    if (!zend_is_callable_ex(&method_name, Z_OBJ_P(object), 0, NULL, NULL, NULL)) {
+        zval_ptr_dtor(&method_name);
+        return LIBPHP_METHOD_NOT_FOUND;
+    }
+
+    libphp_push_frame(&frame);
+
+    zend_try {
+        call_user_function_named(NULL, object, &method_name, retval, param_count, params, named_params);
+        status = libphp_exception_status();
+    } zend_catch {
+        status = libphp_bailout_status();
+    } zend_end_try();
+
+    libphp_pop_frame(&frame);
+    zval_ptr_dtor(&method_name);
+
+    return status;
+}
+
+int libphp_zval_to_string(const zval *pz, zval *result)
+{
+    zend_execute_data frame;
+    int status = LIBPHP_SUCCESS;
+
+    ZVAL_EMPTY_STRING(result);
+
+    if (!libphp_begin()) {
+        return LIBPHP_BAILOUT;
+    }
+
+    libphp_push_frame(&frame);
+
+    zend_try {
+        zend_string *string = zval_get_string((zval *) pz);
+        zval_ptr_dtor(result);
+        ZVAL_STR(result, string);
+        status = libphp_exception_status();
+    } zend_catch {
+        status = libphp_bailout_status();
+    } zend_end_try();
+
+    libphp_pop_frame(&frame);
+
+    return status;
+}
+
+/* Format a value for Display. Unlike libphp_zval_to_string, this leaves the last error and exit
+ * status alone and converts arrays without a warning. An object's __toString() still runs, and
+ * anything it throws is discarded. */
+void libphp_zval_display(const zval *pz, zval *result)
+{
+    zval *value = (zval *) pz;
+    zend_execute_data frame;
+
+    ZVAL_DEREF(value);
+
+    if (Z_TYPE_P(value) == IS_ARRAY) {
+        ZVAL_INTERNED_STR(result, ZSTR_KNOWN(ZEND_STR_ARRAY_CAPITALIZED));
+        return;
+    }
+
+    if (Z_TYPE_P(value) != IS_OBJECT) {
+        ZVAL_STR(result, zval_get_string(value));
+        return;
+    }
+
+    ZVAL_EMPTY_STRING(result);
+
+    if (libphp_bailout_pending || EG(exception)) {
+        return;
+    }
+
+    libphp_push_frame(&frame);
+
+    zend_try {
+        zend_string *string = zval_get_string(value);
+
+        if (EG(exception)) {
+            zend_string_release(string);
+            zend_clear_exception();
+        } else {
+            ZVAL_STR(result, string);
+        }
+    } zend_catch {
+        libphp_catch_bailout();
+        zend_clear_exception();
+    } zend_end_try();
+
+    libphp_pop_frame(&frame);
+}
+
+void libphp_execute_data_function_name(zend_execute_data *execute_data, zval *name)
+{
+    ZVAL_STR_COPY(name, EX(func)->common.function_name);
+}
+
+uint32_t libphp_execute_data_num_args(zend_execute_data *execute_data)
+{
+    return ZEND_CALL_NUM_ARGS(execute_data);
+}
+
+void libphp_execute_data_arg(zend_execute_data *execute_data, uint32_t n, zval *value)
+{
+    ZVAL_COPY_DEREF(value, ZEND_CALL_ARG(execute_data, n + 1));
+}
+
+/* Evaluate the declared default of the function's nth parameter, the way PHP does for parameters
+ * skipped with named arguments. Returns false if it has none or it could not be evaluated. */
+bool libphp_execute_data_default_arg(zend_execute_data *execute_data, uint32_t n, zval *value)
+{
+    zend_function *func = execute_data->func;
+    zend_internal_arg_info *arg_info;
+    bool found = false;
+
+    ZVAL_NULL(value);
+
+    if (n >= func->common.num_args) {
+        return false;
+    }
+
+    arg_info = &func->internal_function.arg_info[n];
+
+    zend_try {
+        found = zend_get_default_from_internal_arg_info(value, arg_info) == SUCCESS;
+
+        /* Defaults that refer to constants are only resolved when used. */
+        if (found && Z_TYPE_P(value) == IS_CONSTANT_AST) {
+            found = zval_update_constant_ex(value, func->common.scope) == SUCCESS;
+        }
+    } zend_catch {
+        libphp_catch_bailout();
+        found = false;
+    } zend_end_try();
+
+    if (!found) {
+        zval_ptr_dtor(value);
+        ZVAL_NULL(value);
+    }
+
+    return found;
+}
+
+void libphp_arg_info_init(zend_internal_arg_info *arg_info, const char *name, uint32_t type_mask, const char *class_name, bool by_ref, bool variadic, const char *default_value)
+{
+    uint32_t flags = _ZEND_ARG_INFO_FLAGS(by_ref, variadic, 0);
+
+    arg_info->name = name;
+    arg_info->default_value = default_value;
+
+    if (class_name) {
+        arg_info->type = (zend_type) ZEND_TYPE_INIT_CLASS_CONST_MASK(class_name, type_mask | flags);
+    } else {
+        arg_info->type = (zend_type) ZEND_TYPE_INIT_MASK(type_mask | flags);
+    }
+}
+
+/* Once the calling PHP code has bailed out there is no frame left to throw into, and the bailout
+ * is about to be raised again anyway, so nothing is thrown. */
+void libphp_throw_exception(const char *message)
+{
+    if (!libphp_bailout_pending) {
+        zend_throw_exception(NULL, message, 0);
+    }
+}
+
+void libphp_throw_argument_count_error(uint32_t min, uint32_t max)
+{
+    if (!libphp_bailout_pending) {
+        zend_wrong_parameters_count_error(min, max);
+    }
+}
+
+void libphp_throw_argument_type_error(uint32_t arg_num, const char *expected, const zval *given)
+{
+    if (!libphp_bailout_pending) {
+        zend_argument_type_error(arg_num, "must be of type %s, %s given", expected, zend_zval_type_name(given));
+    }
+}
+
+void libphp_throw_error(const char *message)
+{
+    if (!libphp_bailout_pending) {
+        zend_throw_error(NULL, "%s", message);
+    }
+}
+
+/* PHP calls Rust functions and methods through these handlers rather than the Rust trampolines
+ * themselves, so that a bailout caught while the trampoline ran is raised again from C, after the
+ * Rust frames have returned. */
+static zif_handler libphp_function_trampoline = NULL;
+static zif_handler libphp_method_trampoline = NULL;
+
+void libphp_set_trampolines(zif_handler function, zif_handler method)
+{
+    libphp_function_trampoline = function;
+    libphp_method_trampoline = method;
+}
+
+static void libphp_call_trampoline(zif_handler trampoline, zend_execute_data *execute_data, zval *return_value)
+{
+    libphp_enter_callback();
+    trampoline(execute_data, return_value);
+    libphp_leave_callback();
+}
+
+void libphp_function_handler(zend_execute_data *execute_data, zval *return_value)
+{
+    libphp_call_trampoline(libphp_function_trampoline, execute_data, return_value);
+}
+
+void libphp_method_handler(zend_execute_data *execute_data, zval *return_value)
+{
+    libphp_call_trampoline(libphp_method_trampoline, execute_data, return_value);
+}
+
+/* Objects of classes registered from Rust carry a pointer to their Rust state,
+ * which is set by the constructor and released with the given callback. */
+typedef struct {
+    void *data;
+    libphp_object_free_callback free;
+    zend_object std;
+} libphp_object;
+
+static zend_object_handlers libphp_object_handlers;
+
+static libphp_object *libphp_object_from(zend_object *object)
+{
+    return (libphp_object *) ((char *) object - XtOffsetOf(libphp_object, std));
+}
+
+static zend_object *libphp_object_create(zend_class_entry *ce)
+{
+    libphp_object *object = zend_object_alloc(sizeof(libphp_object), ce);
+
+    object->data = NULL;
+    object->free = NULL;
+
+    zend_object_std_init(&object->std, ce);
+    object_properties_init(&object->std, ce);
+    object->std.handlers = &libphp_object_handlers;
+
+    return &object->std;
+}
+
+static void libphp_object_free(zend_object *std)
+{
+    libphp_object *object = libphp_object_from(std);
+
+    if (object->data && object->free) {
+        object->free(object->data);
+    }
+
+    object->data = NULL;
+    zend_object_std_dtor(std);
+}
+
+zend_class_entry *libphp_lookup_interface(const char *name)
+{
+    zend_string *class_name = zend_string_init(name, strlen(name), 0);
+    /* Autoloading would run PHP code outside any frame or bailout handler. */
+    zend_class_entry *ce = zend_lookup_class_ex(class_name, NULL, ZEND_FETCH_CLASS_NO_AUTOLOAD);
+
+    zend_string_release(class_name);
+
+    return ce && (ce->ce_flags & ZEND_ACC_INTERFACE) ? ce : NULL;
+}
+
+zend_class_entry *libphp_register_class(const char *name, const zend_function_entry *methods, uint32_t flags, zend_class_entry **interfaces, uint32_t num_interfaces)
+{
+    zend_class_entry ce, *class_entry;
+    zend_string *lowercase_name = zend_string_init(name, strlen(name), 0);
+    bool exists;
+
+    zend_str_tolower(ZSTR_VAL(lowercase_name), ZSTR_LEN(lowercase_name));
+    exists = zend_hash_exists(CG(class_table), lowercase_name);
+
+    zend_string_release(lowercase_name);
+
+    if (exists) {
+        return NULL;
+    }
+
+    if (libphp_object_handlers.offset == 0) {
+        memcpy(&libphp_object_handlers, &std_object_handlers, sizeof(zend_object_handlers));
+        libphp_object_handlers.offset = XtOffsetOf(libphp_object, std);
+        libphp_object_handlers.free_obj = libphp_object_free;
+        /* The Rust state cannot be copied, so these objects cannot be cloned. */
+        libphp_object_handlers.clone_obj = NULL;
+    }
+
+    INIT_CLASS_ENTRY_EX(ce, name, strlen(name), methods);
+    class_entry = zend_register_internal_class_ex(&ce, NULL);
+    class_entry->ce_flags |= flags;
+    class_entry->create_object = libphp_object_create;
+
+    for (uint32_t i = 0; i < num_interfaces; i++) {
+        zend_class_implements(class_entry, 1, interfaces[i]);
+    }
+
+    return class_entry;
+}
+
+/* Class constants and property defaults of internal classes outlive the request,
+ * so strings must be allocated persistently and nothing else may be refcounted. */
+static bool libphp_persistent_zval(zval *dst, const zval *src)
+{
+    switch (Z_TYPE_P(src)) {
+        case IS_NULL:
+        case IS_FALSE:
+        case IS_TRUE:
+        case IS_LONG:
+        case IS_DOUBLE:
+            ZVAL_COPY_VALUE(dst, src);
+            return true;
+        case IS_STRING:
+            ZVAL_STR(dst, zend_string_init(Z_STRVAL_P(src), Z_STRLEN_P(src), 1));
+            return true;
+        default:
+            return false;
+    }
+}
+
+bool libphp_declare_property(zend_class_entry *ce, const char *name, const zval *value)
+{
+    zval property;
+
+    if (!libphp_persistent_zval(&property, value)) {
+        return false;
+    }
+
+    zend_declare_property(ce, name, strlen(name), &property, ZEND_ACC_PUBLIC);
+
+    return true;
+}
+
+bool libphp_declare_constant(zend_class_entry *ce, const char *name, const zval *value)
+{
+    zval constant;
+
+    if (!libphp_persistent_zval(&constant, value)) {
+        return false;
+    }
+
+    zend_declare_class_constant(ce, name, strlen(name), &constant);
+
+    return true;
+}
+
+void libphp_execute_data_scope_name(zend_execute_data *execute_data, zval *name)
+{
+    ZVAL_STR_COPY(name, EX(func)->common.scope->name);
+}
+
+static libphp_object *libphp_execute_data_object(zend_execute_data *execute_data)
+{
+    if (Z_TYPE(EX(This)) != IS_OBJECT || Z_OBJ(EX(This))->handlers != &libphp_object_handlers) {
+        return NULL;
+    }
+
+    return libphp_object_from(Z_OBJ(EX(This)));
+}
+
+void *libphp_execute_data_object_data(zend_execute_data *execute_data)
+{
+    libphp_object *object = libphp_execute_data_object(execute_data);
+
+    return object ? object->data : NULL;
+}
+
+bool libphp_execute_data_set_object_data(zend_execute_data *execute_data, void *data, libphp_object_free_callback free)
+{
+    libphp_object *object = libphp_execute_data_object(execute_data);
+
+    if (!object) {
+        return false;
+    }
+
+    if (object->data && object->free) {
+        object->free(object->data);
+    }
+
+    object->data = data;
+    object->free = free;
+
+    return true;
+}
+
+static libphp_output_callback libphp_output_handler = NULL;
+static void *libphp_output_context = NULL;
+static size_t (*libphp_original_ub_write)(const char *str, size_t len) = NULL;
+static void (*libphp_original_flush)(void *server_context) = NULL;
+
+static size_t libphp_ub_write(const char *str, size_t len)
+{
+    if (libphp_output_handler == NULL) {
+        return libphp_original_ub_write(str, len);
+    }
+
+    libphp_enter_callback();
+    libphp_output_handler(str, len, libphp_output_context);
+    libphp_leave_callback();
+
+    return len;
+}
+
+static void libphp_flush(void *server_context)
+{
+    if (libphp_output_handler == NULL && libphp_original_flush != NULL) {
+        libphp_original_flush(server_context);
+    }
+}
+
+/* php_embed_init() copies the embed SAPI over sapi_module, so the hooks are installed again for
+ * each context, and removed when the handler is cleared (which closing a context does). */
+void libphp_set_output_handler(libphp_output_callback handler, void *context)
+{
+    if (handler && sapi_module.ub_write != libphp_ub_write) {
+        libphp_original_ub_write = sapi_module.ub_write;
+        libphp_original_flush = sapi_module.flush;
+
+        sapi_module.ub_write = libphp_ub_write;
+        sapi_module.flush = libphp_flush;
+    }
+
+    if (!handler && sapi_module.ub_write == libphp_ub_write) {
+        sapi_module.ub_write = libphp_original_ub_write;
+        sapi_module.flush = libphp_original_flush;
+
+        libphp_original_ub_write = NULL;
+        libphp_original_flush = NULL;
+    }
+
+    libphp_output_handler = handler;
+    libphp_output_context = context;
+}
+
+static libphp_error_callback libphp_error_handler = NULL;
+static void *libphp_error_context = NULL;
+static void (*libphp_original_error_cb)(int type, zend_string *error_filename, const uint32_t error_lineno, zend_string *message) = NULL;
+
+static void libphp_error_cb(int type, zend_string *error_filename, const uint32_t error_lineno, zend_string *message)
+{
+    bool display_errors, log_errors;
+
+    /* As with PHP's own display and logging, diagnostics silenced with @ or excluded by
+     * error_reporting are only recorded. */
+    if (libphp_error_handler == NULL || !((EG(error_reporting) & type) || (type & E_CORE))) {
+        libphp_original_error_cb(type, error_filename, error_lineno, message);
+        return;
+    }
+
+    libphp_enter_callback();
+    libphp_error_handler(
+        type,
+        ZSTR_VAL(message),
+        ZSTR_LEN(message),
+        error_filename ? ZSTR_VAL(error_filename) : NULL,
+        error_filename ? ZSTR_LEN(error_filename) : 0,
+        error_lineno,
+        libphp_error_context
+    );
+    libphp_leave_callback();
+
+    /* The handler has already seen the error, so PHP only needs to record it
+     * (for error_get_last()) and bail out if it is fatal. */
+    display_errors = PG(display_errors);
+    log_errors = PG(log_errors);
+    PG(display_errors) = 0;
+    PG(log_errors) = 0;
+
+    zend_try {
+        libphp_original_error_cb(type, error_filename, error_lineno, message);
+    } zend_catch {
+        PG(display_errors) = display_errors;
+        PG(log_errors) = log_errors;
+        zend_bailout();
+    } zend_end_try();
+
+    PG(display_errors) = display_errors;
+    PG(log_errors) = log_errors;
+}
+
+/* zend_startup() resets zend_error_cb, so the hook is installed again for each context, and
+ * removed when the handler is cleared (which closing a context does). */
+void libphp_set_error_handler(libphp_error_callback handler, void *context)
+{
+    if (handler && zend_error_cb != libphp_error_cb) {
+        libphp_original_error_cb = zend_error_cb;
+        zend_error_cb = libphp_error_cb;
+    }
+
+    if (!handler && zend_error_cb == libphp_error_cb) {
+        zend_error_cb = libphp_original_error_cb;
+        libphp_original_error_cb = NULL;
+    }
+
+    libphp_error_handler = handler;
+    libphp_error_context = context;
+}
+
+static char *libphp_ini_entries = NULL;
+static char *libphp_startup_ini_entries = NULL;
+static int (*libphp_original_startup)(sapi_module_struct *sapi_module) = NULL;
+
+static int libphp_embed_startup(sapi_module_struct *sapi_module)
+{
+    /* The entries combined for the previous context are no longer referenced, since
+     * php_embed_init() has just replaced them with its own. */
+    free(libphp_startup_ini_entries);
+    libphp_startup_ini_entries = NULL;
+
+    /* php_embed_init() installs its hard-coded INI entries right before
+     * calling startup, so ours are appended here and take precedence. */
+    if (libphp_ini_entries) {
+        size_t hardcoded_len = sapi_module->ini_entries ? strlen(sapi_module->ini_entries) : 0;
+        size_t entries_len = strlen(libphp_ini_entries);
+        char *entries = malloc(hardcoded_len + entries_len + 1);
+
+        if (entries == NULL) {
+            return FAILURE;
+        }
+
+        if (hardcoded_len > 0) {
+            memcpy(entries, sapi_module->ini_entries, hardcoded_len);
+        }
+
+        memcpy(entries + hardcoded_len, libphp_ini_entries, entries_len + 1);
+
+        sapi_module->ini_entries = entries;
+        libphp_startup_ini_entries = entries;
+    }
+
+    return libphp_original_startup(sapi_module);
+}
+
+void libphp_set_ini_entries(const char *entries)
+{
+    if (libphp_original_startup == NULL) {
+        libphp_original_startup = php_embed_module.startup;
+        php_embed_module.startup = libphp_embed_startup;
+    }
+
+    free(libphp_ini_entries);
+    libphp_ini_entries = entries ? strdup(entries) : NULL;
+}
+
+int libphp_ini_set(const char *name, const char *value)
+{
+    zend_ini_entry *ini_entry = zend_hash_str_find_ptr(EG(ini_directives), name, strlen(name));
+    int status = LIBPHP_SUCCESS;
+
+    if (ini_entry == NULL) {
+        return LIBPHP_INI_UNKNOWN;
+    }
+
+    if (!(ini_entry->modifiable & ZEND_INI_USER)) {
+        return LIBPHP_INI_NOT_MODIFIABLE;
+    }
+
+    if (!libphp_begin()) {
+        return LIBPHP_BAILOUT;
+    }
+
+    /* The directive's modify handler may raise a fatal error. */
+    zend_try {
+        if (zend_alter_ini_entry_chars(ini_entry->name, value, strlen(value), ZEND_INI_USER, ZEND_INI_STAGE_RUNTIME) == FAILURE) {
+            status = LIBPHP_INI_INVALID_VALUE;
+        }
+    } zend_catch {
+        status = libphp_bailout_status();
+    } zend_end_try();
+
+    return status;
+}
+
+bool libphp_ini_get(const char *name, zval *value)
+{
+    zend_ini_entry *ini_entry = zend_hash_str_find_ptr(EG(ini_directives), name, strlen(name));
+
+    if (ini_entry == NULL) {
+        ZVAL_NULL(value);
+        return false;
+    }
+
+    if (ini_entry->value) {
+        ZVAL_STR_COPY(value, ini_entry->value);
+    } else {
+        ZVAL_EMPTY_STRING(value);
+    }
+
+    return true;
+}
+
+bool libphp_get_constant(const char *name, zval *value)
+{
+    zend_string *constant_name = zend_string_init(name, strlen(name), 0);
+    zval *found = NULL;
+
+    ZVAL_NULL(value);
+
+    zend_try {
+        /* Class constants may need to be evaluated, which can throw. Their class is looked up
+         * without autoloading, which could run arbitrary code. */
+        found = zend_get_constant_ex(constant_name, NULL, ZEND_FETCH_CLASS_SILENT | ZEND_FETCH_CLASS_NO_AUTOLOAD);
+
+        if (EG(exception)) {
+            zend_clear_exception();
+            found = NULL;
+        }
+    } zend_catch {
+        libphp_catch_bailout();
+    } zend_end_try();
+
+    zend_string_release(constant_name);
+
+    if (!found) {
+        return false;
+    }
+
+    ZVAL_COPY_OR_DUP(value, found);
+
+    return true;
+}
+
+bool libphp_function_exists(const char *name)
+{
+    if (name[0] == '\\') {
+        name++;
+    }
+
+    zend_string *lowercase_name = zend_string_init(name, strlen(name), 0);
+    bool exists;
+
+    zend_str_tolower(ZSTR_VAL(lowercase_name), ZSTR_LEN(lowercase_name));
+    exists = zend_hash_exists(EG(function_table), lowercase_name);
+    zend_string_release(lowercase_name);
+
+    return exists;
+}
+
+/* Like class_exists($name, false): interfaces and traits don't count, and nothing is autoloaded. */
+bool libphp_class_exists(const char *name)
+{
+    zend_string *class_name = zend_string_init(name, strlen(name), 0);
+    zend_class_entry *ce = zend_lookup_class_ex(class_name, NULL, ZEND_FETCH_CLASS_NO_AUTOLOAD);
+
+    zend_string_release(class_name);
+
+    return ce && !(ce->ce_flags & (ZEND_ACC_INTERFACE | ZEND_ACC_TRAIT));
+}
+
+/* Keys starting with a NUL byte are placeholders for functions and classes that are declared
+ * conditionally at runtime, and aren't defined under that name. */
+static bool libphp_is_runtime_key(const zend_string *key)
+{
+    return key && ZSTR_LEN(key) > 0 && ZSTR_VAL(key)[0] == '\0';
+}
+
+bool libphp_function_table_next(HashPosition *pos, zval *name, bool *user)
+{
+    zval *found;
+
+    while ((found = zend_hash_get_current_data_ex(EG(function_table), pos)) != NULL) {
+        zend_string *key = NULL;
+        zend_ulong index;
+        zend_function *function = Z_PTR_P(found);
+
+        zend_hash_get_current_key_ex(EG(function_table), &key, &index, pos);
+        zend_hash_move_forward_ex(EG(function_table), pos);
+
+        if (libphp_is_runtime_key(key)) {
+            continue;
+        }
+
+        ZVAL_STR_COPY(name, function->common.function_name);
+        *user = function->type == ZEND_USER_FUNCTION;
+
+        return true;
+    }
+
+    return false;
+}
+
+bool libphp_class_table_next(HashPosition *pos, zval *name, bool *user)
+{
+    zval *found;
+
+    while ((found = zend_hash_get_current_data_ex(EG(class_table), pos)) != NULL) {
+        zend_string *key = NULL;
+        zend_ulong index;
+        zend_class_entry *ce = Z_PTR_P(found);
+
+        zend_hash_get_current_key_ex(EG(class_table), &key, &index, pos);
+        zend_hash_move_forward_ex(EG(class_table), pos);
+
+        /* Skip aliases created with class_alias(), which point at a class listed under its own name. */
+        if (libphp_is_runtime_key(key) || (key && !zend_string_equals_ci(key, ce->name))) {
+            continue;
+        }
+
+        ZVAL_STR_COPY(name, ce->name);
+        *user = ce->type == ZEND_USER_CLASS;
+
+        return true;
+    }
+
+    return false;
+}
+
+bool libphp_constant_table_next(HashPosition *pos, zval *name, bool *user)
+{
+    zval *found = zend_hash_get_current_data_ex(EG(zend_constants), pos);
+    zend_constant *constant;
+
+    if (!found) {
+        return false;
+    }
+
+    constant = Z_PTR_P(found);
+    zend_hash_move_forward_ex(EG(zend_constants), pos);
+
+    ZVAL_STR_COPY(name, constant->name);
+    *user = ZEND_CONSTANT_MODULE_NUMBER(constant) == PHP_USER_CONSTANT;
+
+    return true;
+}
+
+/* Resolve a callable once, so that it can be called repeatedly without looking it up again.
+ *
+ * Methods reached through __call() or __callStatic() resolve to a temporary trampoline that
+ * is consumed by the call, so those aren't cached and are resolved again for every call. */
+int libphp_callable_resolve(zval *callable, zend_fcall_info_cache *fcc, zval *name, zval *error)
+{
+    char *error_str = NULL;
+    zend_string *callable_name = NULL;
+    bool is_callable = false;
+    zend_execute_data frame;
+    int status = LIBPHP_SUCCESS;
+
+    ZVAL_EMPTY_STRING(name);
+    ZVAL_NULL(error);
+
+    if (!libphp_begin()) {
+        return LIBPHP_BAILOUT;
+    }
+
+    /* Resolving a static method may autoload its class, which runs PHP code. */
+    libphp_push_frame(&frame);
+
+    zend_try {
+        is_callable = zend_is_callable_ex(callable, NULL, 0, &callable_name, fcc, &error_str);
+        status = libphp_exception_status();
+    } zend_catch {
+        status = libphp_bailout_status();
+    } zend_end_try();
+
+    libphp_pop_frame(&frame);
+
+    if (callable_name) {
+        ZVAL_STR(name, callable_name);
+    }
+
+    if (error_str) {
+        ZVAL_STRING(error, error_str);
+        efree(error_str);
+    }
+
+    if (status != LIBPHP_SUCCESS) {
+        return status;
+    }
+
+    if (!is_callable) {
+        return LIBPHP_FUNCTION_NOT_FOUND;
+    }
+
+    if (fcc->function_handler->common.fn_flags & ZEND_ACC_CALL_VIA_TRAMPOLINE) {
+        zend_release_fcall_info_cache(fcc);
+        fcc->function_handler = NULL;
+    }
+
+    return LIBPHP_SUCCESS;
+}
+
+int libphp_callable_call(zval *callable, const zend_fcall_info_cache *fcc, zval *retval, uint32_t param_count, zval *params, HashTable *named_params)
+{
+    zend_fcall_info_cache call_fcc = *fcc;
+    zend_execute_data frame;
+    int status = LIBPHP_SUCCESS;
+
+    ZVAL_NULL(retval);
+
+    if (!libphp_begin()) {
+        return LIBPHP_BAILOUT;
+    }
+
+    /* Trampolines such as __call() are resolved again for each call, which may autoload. */
+    libphp_push_frame(&frame);
+
+    zend_try {
+        if (call_fcc.function_handler || zend_is_callable_ex(callable, NULL, 0, NULL, &call_fcc, NULL)) {
+            zend_call_known_function(call_fcc.function_handler, call_fcc.object, call_fcc.called_scope, retval, param_count, params, named_params);
+            status = libphp_exception_status();
+        } else {
+            status = EG(exception) ? libphp_exception_status() : LIBPHP_FUNCTION_NOT_FOUND;
+        }
+    } zend_catch {
+        status = libphp_bailout_status();
+    } zend_end_try();
+
+    libphp_pop_frame(&frame);
+
+    return status;
+}
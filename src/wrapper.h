@@ -3,22 +3,145 @@
 #include "main/php.h"
 #include "sapi/embed/php_embed.h"
 #include "Zend/zend_compile.h"
+#include "Zend/zend_exceptions.h"
 #include <Zend/zend_types.h>
 #include <ext/standard/php_var.h>
 #include "zend_smart_str.h"
 #include "main/php_variables.h"
 
+#define LIBPHP_SUCCESS 0
+#define LIBPHP_FILE_NOT_FOUND 1
+#define LIBPHP_PARSE_ERROR 2
+#define LIBPHP_EXCEPTION 3
+#define LIBPHP_BAILOUT 4
+#define LIBPHP_FUNCTION_NOT_FOUND 5
+#define LIBPHP_EXIT 6
+#define LIBPHP_INI_UNKNOWN 7
+#define LIBPHP_INI_NOT_MODIFIABLE 8
+#define LIBPHP_INI_INVALID_VALUE 9
+#define LIBPHP_METHOD_NOT_FOUND 10
+
 uint8_t libphp_zval_get_type(const zval*);
 
-const char* libphp_zval_get_string(zval*);
+zend_long libphp_zval_get_long(const zval *pz);
+double libphp_zval_get_double(const zval *pz);
+bool libphp_zval_is_true(const zval *pz);
+const char *libphp_zval_string_bytes(const zval *pz, size_t *len);
+
+void libphp_zval_ptr_dtor(zval *pz);
+void libphp_zval_copy(zval *dst, zval *src);
 
-const char* libphp_var_export(zval *pz);
+void libphp_var_export(zval *pz, zval *result);
+uint32_t libphp_zval_refcount(const zval *pz);
+size_t libphp_memory_usage(void);
 
 void libphp_zval_create_string(zval *pz, const char *str);
 void libphp_zval_create_long(zval *pz, long l);
+void libphp_zval_create_null(zval *pz);
+void libphp_zval_create_bool(zval *pz, bool b);
+void libphp_zval_create_double(zval *pz, double d);
+void libphp_zval_create_stringl(zval *pz, const char *str, size_t len);
+void libphp_zval_create_reference(zval *pz, zval *value);
+void libphp_zval_create_array(zval *pz, HashTable *ht);
+void libphp_zval_copy_deref(zval *dst, const zval *src);
+
+void libphp_array_addref(HashTable *ht);
+void libphp_array_release(HashTable *ht);
+void libphp_array_separate(HashTable **ht);
+zval *libphp_array_lookup(HashTable *ht, const zval *key);
+bool libphp_array_get(HashTable *ht, const zval *key, zval *value);
+void libphp_array_update(HashTable *ht, const zval *key, zval *value);
+bool libphp_array_push(HashTable *ht, zval *value);
+bool libphp_array_remove(HashTable *ht, const zval *key, zval *removed);
 
 zend_string* libphp_zend_string_init();
+zend_string *libphp_string_create(const char *str, size_t len);
+const char *libphp_string_bytes(const zend_string *s, size_t *len);
+void libphp_string_addref(zend_string *s);
+void libphp_string_release(zend_string *s);
+void libphp_zval_create_str(zval *pz, zend_string *s);
+zend_string *libphp_zval_get_str(const zval *pz);
 
 void libphp_register_variable(const char *key, zval *value);
 
-void libphp_register_constant(const char *name, zval *value);
\ No newline at end of file
+bool libphp_get_global(const char *name, zval *value);
+void libphp_set_global(const char *name, zval *value);
+bool libphp_unset_global(const char *name);
+bool libphp_symbol_table_next(HashPosition *pos, zval *name, zval **value);
+bool libphp_get_constant(const char *name, zval *value);
+bool libphp_function_exists(const char *name);
+bool libphp_class_exists(const char *name);
+bool libphp_function_table_next(HashPosition *pos, zval *name, bool *user);
+bool libphp_class_table_next(HashPosition *pos, zval *name, bool *user);
+bool libphp_constant_table_next(HashPosition *pos, zval *name, bool *user);
+void libphp_register_constant(const char *name, zval *value);
+
+int libphp_execute_file(const char *filename, zval *retval);
+int libphp_eval_string(const char *code, const char *name, zval *retval);
+int libphp_execute_string(const char *code, size_t len, const char *filename, bool after_open_tag, zval *retval);
+int libphp_compile_file(const char *filename, zend_op_array **op_array);
+int libphp_compile_string(const char *code, size_t len, const char *filename, bool after_open_tag, zend_op_array **op_array);
+int libphp_execute_op_array(zend_op_array *op_array, zval *retval);
+void libphp_destroy_op_array(zend_op_array *op_array);
+int libphp_lint_file(const char *filename);
+int libphp_lint_string(const char *code, size_t len, const char *filename);
+int libphp_call_function(const char *name, zval *retval, uint32_t param_count, zval *params, HashTable *named_params);
+
+int libphp_exit_status(void);
+uint32_t libphp_last_error_lineno(void);
+bool libphp_last_error_message(zval *message);
+
+bool libphp_exception_take(zval *exception);
+void libphp_exception_property(zval *exception, const char *name, zval *value);
+void libphp_exception_trace_string(zval *exception, zval *trace);
+
+void libphp_object_class_name(zval *object, zval *name);
+
+bool libphp_object_instance_of(const zval *object, const char *class_name);
+bool libphp_object_method_exists(const zval *object, const char *name);
+bool libphp_object_has_property(const zval *object, const char *name);
+int libphp_object_read_property(const zval *object, const char *name, zval *value);
+int libphp_object_write_property(const zval *object, const char *name, zval *value);
+int libphp_call_method(zval *object, const char *name, zval *retval, uint32_t param_count, zval *params, HashTable *named_params);
+int libphp_zval_to_string(const zval *pz, zval *result);
+void libphp_zval_display(const zval *pz, zval *result);
+int libphp_callable_resolve(zval *callable, zend_fcall_info_cache *fcc, zval *name, zval *error);
+int libphp_callable_call(zval *callable, const zend_fcall_info_cache *fcc, zval *retval, uint32_t param_count, zval *params, HashTable *named_params);
+
+void libphp_execute_data_function_name(zend_execute_data *execute_data, zval *name);
+uint32_t libphp_execute_data_num_args(zend_execute_data *execute_data);
+void libphp_execute_data_arg(zend_execute_data *execute_data, uint32_t n, zval *value);
+bool libphp_execute_data_default_arg(zend_execute_data *execute_data, uint32_t n, zval *value);
+
+void libphp_arg_info_init(zend_internal_arg_info *arg_info, const char *name, uint32_t type_mask, const char *class_name, bool by_ref, bool variadic, const char *default_value);
+
+void libphp_throw_exception(const char *message);
+void libphp_throw_argument_count_error(uint32_t min, uint32_t max);
+void libphp_throw_argument_type_error(uint32_t arg_num, const char *expected, const zval *given);
+void libphp_throw_error(const char *message);
+
+void libphp_set_trampolines(zif_handler function, zif_handler method);
+void libphp_function_handler(zend_execute_data *execute_data, zval *return_value);
+void libphp_method_handler(zend_execute_data *execute_data, zval *return_value);
+
+typedef void (*libphp_object_free_callback)(void *data);
+
+zend_class_entry *libphp_lookup_interface(const char *name);
+zend_class_entry *libphp_register_class(const char *name, const zend_function_entry *methods, uint32_t flags, zend_class_entry **interfaces, uint32_t num_interfaces);
+bool libphp_declare_property(zend_class_entry *ce, const char *name, const zval *value);
+bool libphp_declare_constant(zend_class_entry *ce, const char *name, const zval *value);
+void libphp_execute_data_scope_name(zend_execute_data *execute_data, zval *name);
+void *libphp_execute_data_object_data(zend_execute_data *execute_data);
+bool libphp_execute_data_set_object_data(zend_execute_data *execute_data, void *data, libphp_object_free_callback free);
+
+typedef void (*libphp_output_callback)(const char *str, size_t len, void *context);
+
+void libphp_set_output_handler(libphp_output_callback handler, void *context);
+
+typedef void (*libphp_error_callback)(int type, const char *message, size_t message_len, const char *filename, size_t filename_len, uint32_t lineno, void *context);
+
+void libphp_set_error_handler(libphp_error_callback handler, void *context);
+
+void libphp_set_ini_entries(const char *entries);
+int libphp_ini_set(const char *name, const char *value);
+bool libphp_ini_get(const char *name, zval *value);
\ No newline at end of file